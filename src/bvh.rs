@@ -0,0 +1,218 @@
+use super::*;
+
+type BoxedElement<V> = Box<dyn SceneElement<V = V>>;
+
+type IdentifiedElement<V> = (ElementId, BoxedElement<V>);
+
+const MAX_LEAF_SIZE: usize = 4;
+
+enum Node<V: Vector> {
+    Leaf {
+        bounds: BoundingBox<V>,
+        elements: std::ops::Range<usize>,
+    },
+    Branch {
+        bounds: BoundingBox<V>,
+        // the left child always directly follows its parent
+        right: usize,
+    },
+}
+
+impl<V: Vector> Node<V> {
+    fn bounds(&self) -> &BoundingBox<V> {
+        match self {
+            Node::Leaf { bounds, .. } | Node::Branch { bounds, .. } => bounds,
+        }
+    }
+}
+
+/// A bounding volume hierarchy over a fixed set of elements.
+///
+/// Elements without bounds can't be sorted into the hierarchy, so they're tested against every ray.
+pub struct BvhScene<V: Vector> {
+    nodes: Vec<Node<V>>,
+    elements: Vec<IdentifiedElement<V>>,
+    unbounded: Vec<IdentifiedElement<V>>,
+}
+
+impl<V: Vector> BvhScene<V> {
+    /// numbers the elements from 1 in the given order, for their `ElementId`s
+    pub fn new(elements: Vec<BoxedElement<V>>) -> Self {
+        Self::with_ids((1..).map(ElementId).zip(elements).collect())
+    }
+
+    /// keeps the ids the elements already have, e.g. from being added to a `VecScene`
+    pub fn with_ids(elements: Vec<IdentifiedElement<V>>) -> Self {
+        let (bounded, unbounded): (Vec<_>, Vec<_>) = elements
+            .into_iter()
+            .partition(|(_, e)| e.bounds().is_some());
+        let mut entries: Vec<_> = bounded
+            .into_iter()
+            .map(|(id, e)| (e.bounds().unwrap(), (id, e)))
+            .collect();
+
+        let mut nodes = vec![];
+        if !entries.is_empty() {
+            Self::build(&mut nodes, &mut entries, 0);
+        }
+
+        Self {
+            nodes,
+            elements: entries.into_iter().map(|(_, e)| e).collect(),
+            unbounded,
+        }
+    }
+
+    fn build(
+        nodes: &mut Vec<Node<V>>,
+        entries: &mut [(BoundingBox<V>, IdentifiedElement<V>)],
+        offset: usize,
+    ) {
+        let bounds = entries
+            .iter()
+            .map(|(b, _)| *b)
+            .reduce(|l, r| l.union(&r))
+            .unwrap();
+
+        if entries.len() <= MAX_LEAF_SIZE {
+            nodes.push(Node::Leaf {
+                bounds,
+                elements: offset..offset + entries.len(),
+            });
+            return;
+        }
+
+        // split at the median along the axis where the centers are spread out the most
+        let axis = BoundingBox::around_points(entries.iter().map(|(b, _)| b.center()))
+            .unwrap()
+            .longest_axis();
+        entries.sort_by(|(l, _), (r, _)| {
+            let l = l.center().component(axis);
+            let r = r.center().component(axis);
+            l.partial_cmp(&r).unwrap()
+        });
+        let middle = entries.len() / 2;
+
+        let index = nodes.len();
+        nodes.push(Node::Branch { bounds, right: 0 });
+        let (left, right) = entries.split_at_mut(middle);
+        Self::build(nodes, left, offset);
+        let right_index = nodes.len();
+        Self::build(nodes, right, offset + middle);
+        nodes[index] = Node::Branch {
+            bounds,
+            right: right_index,
+        };
+    }
+}
+
+impl<V: Vector> SceneElement for BvhScene<V> {
+    type V = V;
+
+    fn first_intersection(
+        &self,
+        ray: Ray<Self::V>,
+        near_clipping: Component,
+    ) -> ElementIntersection<'_, Self::V> {
+        let mut closest: ElementIntersection<V> = self
+            .unbounded
+            .iter()
+            .filter_map(|(id, e)| Some(nested(e.first_intersection(ray, near_clipping)?, *id)))
+            .min_by(|l, r| l.distance.partial_cmp(&r.distance).unwrap());
+
+        let mut stack = vec![];
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            count_intersection_tests(1);
+            let far = closest.as_ref().map_or(Component::INFINITY, |i| i.distance);
+            if node
+                .bounds()
+                .intersection_range(&ray, near_clipping, far)
+                .is_none()
+            {
+                continue;
+            }
+
+            match node {
+                Node::Leaf { elements, .. } => {
+                    for (id, element) in &self.elements[elements.clone()] {
+                        if let Some(intersection) = element.first_intersection(ray, near_clipping) {
+                            let intersection = nested(intersection, *id);
+                            if closest
+                                .as_ref()
+                                .is_none_or(|c| intersection.distance < c.distance)
+                            {
+                                closest = Some(intersection);
+                            }
+                        }
+                    }
+                }
+                Node::Branch { right, .. } => {
+                    stack.push(*right);
+                    stack.push(index + 1);
+                }
+            }
+        }
+
+        closest
+    }
+
+    fn occluded(
+        &self,
+        ray: Ray<Self::V>,
+        near_clipping: Component,
+        far_clipping: Component,
+    ) -> bool {
+        if self
+            .unbounded
+            .iter()
+            .any(|(_, e)| e.occluded(ray, near_clipping, far_clipping))
+        {
+            return true;
+        }
+
+        let mut stack = vec![];
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            count_intersection_tests(1);
+            if node
+                .bounds()
+                .intersection_range(&ray, near_clipping, far_clipping)
+                .is_none()
+            {
+                continue;
+            }
+
+            match node {
+                Node::Leaf { elements, .. } => {
+                    if self.elements[elements.clone()]
+                        .iter()
+                        .any(|(_, e)| e.occluded(ray, near_clipping, far_clipping))
+                    {
+                        return true;
+                    }
+                }
+                Node::Branch { right, .. } => {
+                    stack.push(*right);
+                    stack.push(index + 1);
+                }
+            }
+        }
+
+        false
+    }
+
+    fn bounds(&self) -> Option<BoundingBox<Self::V>> {
+        if self.unbounded.is_empty() {
+            self.nodes.first().map(|n| *n.bounds())
+        } else {
+            None
+        }
+    }
+}
@@ -45,6 +45,11 @@ impl Color {
         self.red.max(self.green).max(self.blue)
     }
 
+    /// perceived brightness, using the Rec. 709 weights
+    pub fn luminance(&self) -> Component {
+        0.2126 * self.red + 0.7152 * self.green + 0.0722 * self.blue
+    }
+
     pub fn clamped(&self) -> Self {
         Self {
             red: self.red.clamp(0.0, 1.0),
@@ -1,7 +1,9 @@
 use super::*;
 use image_lib::*;
-use std::io::Write;
+use std::fs::File;
+use std::io::{BufReader, Write};
 use std::iter::*;
+use std::path::Path;
 
 pub struct Image {
     pixels: Vec<Color>,
@@ -18,6 +20,67 @@ impl Image {
         }
     }
 
+    /// loads any format the `image` crate supports, mapping channels to `0.0..=1.0`
+    pub fn open<P: AsRef<Path>>(path: P) -> ImageResult<Self> {
+        let loaded = image_lib::open(path)?.into_rgba8();
+        let (width, height) = loaded.dimensions();
+
+        fn component(value: u8) -> Component {
+            value as Component / 255.0
+        }
+        let pixels = loaded
+            .pixels()
+            .map(|px| {
+                let [red, green, blue, alpha] = px.0;
+                Color::new(
+                    component(red),
+                    component(green),
+                    component(blue),
+                    component(alpha),
+                )
+            })
+            .collect();
+
+        Ok(Self {
+            pixels,
+            width: width as usize,
+            height: height as usize,
+        })
+    }
+
+    /// loads a Radiance HDR (`.hdr`) file, keeping its linear, unbounded values
+    pub fn open_hdr<P: AsRef<Path>>(path: P) -> ImageResult<Self> {
+        let decoder = hdr::HdrDecoder::new(BufReader::new(File::open(path)?))?;
+        let metadata = decoder.metadata();
+        let pixels = decoder
+            .read_image_hdr()?
+            .into_iter()
+            .map(|px| {
+                let [red, green, blue] = px.0;
+                Color::new(red, green, blue, 1.0)
+            })
+            .collect();
+
+        Ok(Self {
+            pixels,
+            width: metadata.width as usize,
+            height: metadata.height as usize,
+        })
+    }
+
+    /// loads the first RGBA layer of an OpenEXR (`.exr`) file, keeping its linear, unbounded values
+    pub fn open_exr<P: AsRef<Path>>(path: P) -> exr::error::Result<Self> {
+        let loaded = exr::prelude::read_first_rgba_layer_from_file(
+            path,
+            |resolution, _| Image::new(resolution.width(), resolution.height()),
+            |image: &mut Image, position, (red, green, blue, alpha): (f32, f32, f32, f32)| {
+                let index = image.index(position.x(), position.y());
+                image.pixels[index] = Color::new(red, green, blue, alpha);
+            },
+        )?;
+        Ok(loaded.layer_data.channel_data.pixels)
+    }
+
     pub fn width(&self) -> usize {
         self.width
     }
@@ -30,6 +93,10 @@ impl Image {
         x + y * self.width
     }
 
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        self.pixels[self.index(x, y)]
+    }
+
     pub fn row_mut(&mut self, y: usize) -> &mut [Color] {
         let range = self.index(0, y)..self.index(0, y + 1);
         &mut self.pixels[range]
@@ -40,7 +107,7 @@ impl Image {
     }
 
     pub fn write_png<W: Write>(&self, target: W) -> ImageResult<()> {
-        let encoder = png::PNGEncoder::new(target);
+        let encoder = png::PngEncoder::new(target);
 
         fn byte(value: Component) -> u8 {
             (value * 255.0) as u8
@@ -0,0 +1,26 @@
+use super::*;
+
+/// Shades surfaces by how open the hemisphere above them is, ignoring materials and lights, for a quick look at the geometry.
+///
+/// Each sample casts one ray in a cosine-weighted direction, so crevices and contact points darken
+/// while open surfaces stay white.
+pub struct AmbientOcclusion {
+    /// how far away surfaces still occlude; infinite for all of them
+    pub distance: Component,
+}
+
+impl<E: Scene<V = Vec3>> Integrator<E> for AmbientOcclusion {
+    fn radiance(&self, scene: &E, ray: Ray<Vec3>, options: &TracingOptions) -> Color {
+        let hit = match first_visible_intersection(scene, ray, options, RayKind::Camera) {
+            Some(intersection) => intersection.data.hit,
+            None => return options.background_color,
+        };
+        let frame = Frame::facing(&hit);
+        let probe = hit.next_ray(frame.to_world(cosine_weighted_hemisphere()));
+        if scene.occluded(probe, options.near_clipping, self.distance) {
+            Color::black()
+        } else {
+            Color::white()
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use super::*;
+
+/// Shows something other than shading, for finding out why a render looks wrong.
+///
+/// Each view shows a property of the first surface camera rays see, and the background elsewhere.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DebugView {
+    /// the normal used for shading, mapping each axis from `-1.0..=1.0` to a color channel
+    ShadingNormal,
+    /// the actual orientation of the surface, mapped like `ShadingNormal`
+    GeometricNormal,
+    /// texture coordinates as red and green, wrapped into `0.0..1.0` like repeating textures
+    Uv,
+    /// the distance along the ray, from black at the camera to white at `far`
+    Depth { far: Component },
+    /// a color for each element, e.g. to check how a mesh was split up or tell instances apart
+    ElementId,
+    /// a color for each material, like `ElementId`
+    MaterialId,
+}
+
+impl<E: Scene> Integrator<E> for DebugView {
+    fn radiance(&self, scene: &E, ray: Ray<E::V>, options: &TracingOptions) -> Color {
+        let intersection = match first_visible_intersection(scene, ray, options, RayKind::Camera) {
+            Some(intersection) => intersection,
+            None => return options.background_color,
+        };
+        let SurfaceHit {
+            hit,
+            element,
+            material_id,
+            ..
+        } = intersection.data;
+        match *self {
+            DebugView::ShadingNormal => direction_color(hit.normal),
+            DebugView::GeometricNormal => direction_color(hit.geometric_normal),
+            DebugView::Uv => {
+                Color::new(hit.uv.x.rem_euclid(1.0), hit.uv.y.rem_euclid(1.0), 0.0, 1.0)
+            }
+            DebugView::Depth { far } => {
+                let depth = (intersection.distance / far).min(1.0);
+                Color::new(depth, depth, depth, 1.0)
+            }
+            DebugView::ElementId => false_color(element.0 as usize),
+            DebugView::MaterialId => false_color(material_id.0 as usize),
+        }
+    }
+}
+
+/// Shows how many shapes and bounding boxes are tested against the rays the integrator casts for each sample,
+/// as a heatmap up to `max_tests`, to find what makes a scene slow to render.
+pub struct RayCost<E: Scene> {
+    pub integrator: Box<dyn Integrator<E>>,
+    pub max_tests: usize,
+}
+
+impl<E: Scene> Integrator<E> for RayCost<E> {
+    fn radiance(&self, scene: &E, ray: Ray<E::V>, options: &TracingOptions) -> Color {
+        let before = intersection_tests();
+        self.integrator.radiance(scene, ray, options);
+        let tests = intersection_tests() - before;
+        heatmap(tests as Component / self.max_tests as Component)
+    }
+}
+
+/// Shows how often paths bounce before they end, as a heatmap up to `TracingOptions::max_bounces`.
+///
+/// Paths follow the materials like path tracing, ending at the bounce limits and by russian roulette, but gather no light.
+pub struct BounceHeatmap;
+
+impl<E: Scene> Integrator<E> for BounceHeatmap {
+    fn radiance(&self, scene: &E, mut ray: Ray<E::V>, options: &TracingOptions) -> Color {
+        let mut kind = RayKind::Camera;
+        let mut throughput = Color::white();
+        let mut bounces = BounceCounts::default();
+        while let Some(intersection) = first_visible_intersection(scene, ray, options, kind) {
+            let behavior = intersection.data.behavior();
+            let next_bounce = match behavior.next_bounce {
+                Some(next_bounce) => next_bounce,
+                None => break,
+            };
+            if !bounces.add(behavior.lobe, options) {
+                break;
+            }
+            throughput *= behavior.color;
+            if !survives_roulette(&mut throughput, &bounces, options) {
+                break;
+            }
+            kind = ray_kind(behavior.lobe);
+            ray = next_bounce;
+        }
+        heatmap(bounces.total as Component / options.max_bounces.max(1) as Component)
+    }
+}
+
+/// maps the first three axes from `-1.0..=1.0` to red, green and blue
+fn direction_color<V: Vector>(direction: Normalized<V>) -> Color {
+    let channel = |axis: usize| {
+        if axis < V::DIMENSIONS {
+            direction.component(axis) * 0.5 + 0.5
+        } else {
+            0.0
+        }
+    };
+    Color::new(channel(0), channel(1), channel(2), 1.0)
+}
+
+/// a bright, arbitrary color for the id, with neighboring ids looking nothing alike
+fn false_color(id: usize) -> Color {
+    // scramble the bits, following splitmix64's finalizer
+    let mut bits = id as u64;
+    bits = (bits ^ (bits >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    bits = (bits ^ (bits >> 27)).wrapping_mul(0x94d049bb133111eb);
+    bits ^= bits >> 31;
+    let channel = |shift: u32| 0.2 + 0.8 * ((bits >> shift) & 0xff) as Component / 255.0;
+    Color::new(channel(0), channel(8), channel(16), 1.0)
+}
+
+/// blue through green to red for `0.0..=1.0`, and white beyond so what goes over stands out
+fn heatmap(value: Component) -> Color {
+    const STOPS: [(Component, Component, Component); 5] = [
+        (0.0, 0.0, 1.0),
+        (0.0, 1.0, 1.0),
+        (0.0, 1.0, 0.0),
+        (1.0, 1.0, 0.0),
+        (1.0, 0.0, 0.0),
+    ];
+    if value > 1.0 {
+        return Color::white();
+    }
+    let position = value.max(0.0) * (STOPS.len() - 1) as Component;
+    let index = (position as usize).min(STOPS.len() - 2);
+    let t = position - index as Component;
+    let ((r0, g0, b0), (r1, g1, b1)) = (STOPS[index], STOPS[index + 1]);
+    Color::new(
+        r0 + (r1 - r0) * t,
+        g0 + (g1 - g0) * t,
+        b0 + (b1 - b0) * t,
+        1.0,
+    )
+}
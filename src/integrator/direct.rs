@@ -0,0 +1,32 @@
+use super::*;
+
+/// Only the light reaching the first surface hit straight from the lights, without any bounces.
+///
+/// Mirrors and glass stay dark apart from the lights they can evaluate; useful for checking how a scene is lit.
+pub struct DirectLighting;
+
+impl<E: Scene> Integrator<E> for DirectLighting {
+    fn radiance(&self, scene: &E, ray: Ray<E::V>, options: &TracingOptions) -> Color {
+        let intersection = match first_visible_intersection(scene, ray, options, RayKind::Camera) {
+            Some(intersection) => intersection,
+            None => {
+                let mut escaped = options.background_color;
+                for light in scene.lights() {
+                    escaped += light.escaped_radiance(ray.direction);
+                }
+                escaped.alpha = options.background_color.alpha;
+                return escaped;
+            }
+        };
+        let SurfaceHit {
+            hit,
+            material,
+            light_links,
+            ..
+        } = intersection.data;
+        let mut color = material.behavior(hit).emission
+            + direct_lighting(scene, &hit, material, light_links, options, false);
+        color.alpha = 1.0;
+        color
+    }
+}
@@ -0,0 +1,115 @@
+use super::*;
+
+/// where a path bounced off a surface the lights were sampled at, for weighing the light it finds next
+struct Scattering<V: Vector> {
+    point: V,
+    normal: Normalized<V>,
+    /// the pdf of the material bouncing along the path
+    pdf: Component,
+}
+
+/// Path tracing that finds light both with shadow rays and by bouncing into it, weighing the two with multiple importance sampling.
+///
+/// Shadow rays do best for small lights and rough surfaces, bounces for large lights and glossy ones;
+/// combining them keeps the noise down for both, e.g. for highlights of lights on rough metal.
+pub struct MisPathTracer;
+
+impl<E: Scene> Integrator<E> for MisPathTracer {
+    fn radiance(&self, scene: &E, mut ray: Ray<E::V>, options: &TracingOptions) -> Color {
+        let mut kind = RayKind::Camera;
+        let mut color = Color::black();
+        let mut alpha = options.background_color.alpha;
+        let mut throughput = Color::white();
+        // `None` after the camera and specular bounces, where lights can only be found by bouncing into them
+        let mut scattering: Option<Scattering<E::V>> = None;
+        let mut light_links: Option<&LightLinks> = None;
+        let mut bounces = BounceCounts::default();
+
+        loop {
+            let intersection = match first_visible_intersection(scene, ray, options, kind) {
+                Some(intersection) => intersection,
+                None => {
+                    let mut escaped = options.background_color;
+                    for (index, light) in scene.lights().iter().enumerate() {
+                        let id = LightId(index);
+                        if is_linked(light_links, id) {
+                            escaped += light.escaped_radiance(ray.direction)
+                                * bounce_weight(scene, id, &**light, &scattering, &ray, options);
+                        }
+                    }
+                    color += throughput * escaped;
+                    break;
+                }
+            };
+            alpha = 1.0;
+
+            let SurfaceHit {
+                hit,
+                material,
+                light,
+                light_links: hit_light_links,
+                ..
+            } = intersection.data;
+            let behavior = material.behavior(hit);
+            color +=
+                throughput * direct_lighting(scene, &hit, material, hit_light_links, options, true);
+            match light {
+                Some((id, light)) if is_linked(light_links, id) => {
+                    color += throughput
+                        * behavior.emission
+                        * bounce_weight(scene, id, light, &scattering, &ray, options);
+                }
+                Some(_) => {}
+                None => color += throughput * behavior.emission,
+            }
+
+            let next_bounce = match behavior.next_bounce {
+                Some(next_bounce) => next_bounce,
+                None => break,
+            };
+            if !bounces.add(behavior.lobe, options) {
+                break;
+            }
+            throughput *= behavior.color;
+            if !survives_roulette(&mut throughput, &bounces, options) {
+                break;
+            }
+
+            // the material's pdf stands for the lobes light sampling is weighed against, not specular ones mixed in
+            let pdf = material.pdf(&hit, next_bounce.direction);
+            scattering = if !behavior.is_specular && pdf > 0.0 {
+                Some(Scattering {
+                    point: hit.intersection,
+                    normal: hit.normal,
+                    pdf,
+                })
+            } else {
+                None
+            };
+            kind = ray_kind(behavior.lobe);
+            light_links = hit_light_links;
+            ray = next_bounce;
+        }
+
+        color.alpha = alpha;
+        color
+    }
+}
+
+/// how much to count light found by bouncing into a light along the ray, rather than with a shadow ray from where it bounced
+fn bounce_weight<E: Scene>(
+    scene: &E,
+    id: LightId,
+    light: &dyn Light<V = E::V>,
+    scattering: &Option<Scattering<E::V>>,
+    ray: &Ray<E::V>,
+    options: &TracingOptions,
+) -> Component {
+    match scattering {
+        Some(s) => {
+            let probability = selection_probability(scene, id, s.point, s.normal, options);
+            mis_weight(s.pdf, probability * light.pdf(s.point, ray.direction))
+        }
+        None => 1.0,
+    }
+}
@@ -0,0 +1,213 @@
+mod ambient_occlusion;
+mod debug;
+mod direct;
+mod mis;
+mod path;
+mod whitted;
+
+pub use ambient_occlusion::*;
+pub use debug::*;
+pub use direct::*;
+pub use mis::*;
+pub use path::*;
+pub use whitted::*;
+
+use super::*;
+use rand::*;
+
+/// A way of estimating the light that arrives at the camera along a ray, i.e. of solving the rendering equation.
+///
+/// `Raytracer` holds one as a trait object, so the algorithm can be picked at runtime.
+pub trait Integrator<E: Scene>: Send + Sync {
+    /// The light arriving along a ray from the camera, with some randomness that averages out over many samples.
+    ///
+    /// The resulting alpha is that of the background if nothing is hit, and 1 otherwise,
+    /// so averaging over many samples gives the pixel's coverage.
+    fn radiance(&self, scene: &E, ray: Ray<E::V>, options: &TracingOptions) -> Color;
+}
+
+/// how often a path has bounced in each way
+#[derive(Default)]
+struct BounceCounts {
+    total: usize,
+    diffuse: usize,
+    glossy: usize,
+    transmission: usize,
+}
+
+impl BounceCounts {
+    /// counts another bounce, unless that would go over the limits
+    fn add(&mut self, lobe: Lobe, options: &TracingOptions) -> bool {
+        let (count, limit) = match lobe {
+            Lobe::Diffuse => (&mut self.diffuse, options.max_diffuse_bounces),
+            Lobe::Glossy => (&mut self.glossy, options.max_glossy_bounces),
+            Lobe::Transmission => (&mut self.transmission, options.max_transmission_bounces),
+        };
+        if *count >= limit || self.total >= options.max_bounces {
+            return false;
+        }
+        *count += 1;
+        self.total += 1;
+        true
+    }
+}
+
+/// how much shorter shadow rays are than the distance to the light, so they don't hit whatever the light sits on
+const SHADOW_RAY_SHORTENING: Component = 1e-4;
+
+/// the minimum distance to advance past a hit hidden from a ray before looking for the next one
+const HIDDEN_HIT_STEP: Component = 1e-4;
+
+/// the kind of ray leaving a surface after bouncing off it in the given way
+fn ray_kind(lobe: Lobe) -> RayKind {
+    match lobe {
+        Lobe::Transmission => RayKind::Refraction,
+        Lobe::Diffuse | Lobe::Glossy => RayKind::Reflection,
+    }
+}
+
+/// whether the light illuminates a surface with the given links, `None` standing for all lights
+fn is_linked(light_links: Option<&LightLinks>, light: LightId) -> bool {
+    light_links.is_none_or(|links| links.includes(light))
+}
+
+/// Ends paths carrying little light at random once they've bounced often enough, making up for it in those that go on.
+///
+/// `false` if the path ends.
+fn survives_roulette(
+    throughput: &mut Color,
+    bounces: &BounceCounts,
+    options: &TracingOptions,
+) -> bool {
+    if bounces.total <= options.russian_roulette_depth {
+        return true;
+    }
+    let survival = throughput.brightness().min(1.0);
+    if thread_rng().gen::<Component>() >= survival {
+        return false;
+    }
+    *throughput /= survival;
+    true
+}
+
+/// the closest hit that rays of the given kind can see, continuing past those hidden from them
+fn first_visible_intersection<'a, E: Scene>(
+    scene: &'a E,
+    ray: Ray<E::V>,
+    options: &TracingOptions,
+    kind: RayKind,
+) -> ElementIntersection<'a, E::V> {
+    let step = options.near_clipping.max(HIDDEN_HIT_STEP);
+    let mut near_clipping = options.near_clipping;
+    loop {
+        let intersection = scene.first_intersection(ray, near_clipping)?;
+        if intersection.data.visibility.sees(kind) {
+            return Some(intersection);
+        }
+        near_clipping = intersection.distance + step;
+    }
+}
+
+/// Light reflected straight from the scene's lights, casting shadow rays to those picked by `options.light_selection`.
+///
+/// Lights left out by `light_links` can still be picked, but give no light.
+/// With `is_weighted`, each light's contribution is weighed against the material bouncing towards it,
+/// for combining both with multiple importance sampling.
+fn direct_lighting<E: Scene>(
+    scene: &E,
+    hit: &Hit<E::V>,
+    material: &dyn Material<E::V>,
+    light_links: Option<&LightLinks>,
+    options: &TracingOptions,
+    is_weighted: bool,
+) -> Color {
+    let lights = scene.lights();
+    let contribution = |index: usize, probability: Component| {
+        let (light, id) = (&*lights[index], LightId(index));
+        if !is_linked(light_links, id) {
+            return Color::black();
+        }
+        let sample = match light.sample(hit.intersection) {
+            Some(sample) => sample,
+            None => return Color::black(),
+        };
+        let reflected = material.evaluate(hit, sample.direction) * sample.radiance;
+        if reflected.brightness() <= 0.0 || !is_visible(scene, hit, id, &sample, options) {
+            return Color::black();
+        }
+        let weight = if is_weighted {
+            mis_weight(
+                probability * sample.pdf,
+                material.pdf(hit, sample.direction),
+            )
+        } else {
+            1.0
+        };
+        reflected * (weight / probability)
+    };
+
+    let picked = match options.light_selection {
+        LightSelection::All => {
+            return (0..lights.len())
+                .map(|index| contribution(index, 1.0))
+                .fold(Color::black(), |sum, c| sum + c);
+        }
+        LightSelection::Power => scene.light_sampler().sample_by_power(),
+        LightSelection::Tree => scene
+            .light_sampler()
+            .sample_by_importance(hit.intersection, hit.normal),
+    };
+    match picked {
+        Some((index, probability)) => contribution(index, probability),
+        None => Color::black(),
+    }
+}
+
+/// how likely `direct_lighting` is to pick the light at a hit with the given position and normal
+fn selection_probability<E: Scene>(
+    scene: &E,
+    light: LightId,
+    point: E::V,
+    normal: Normalized<E::V>,
+    options: &TracingOptions,
+) -> Component {
+    match options.light_selection {
+        LightSelection::All => 1.0,
+        LightSelection::Power => scene.light_sampler().power_probability(light),
+        LightSelection::Tree => scene
+            .light_sampler()
+            .importance_probability(light, point, normal),
+    }
+}
+
+/// Veach's power heuristic: how much to count a sample taken with the given pdf,
+/// when another technique could have taken it with `other_pdf`.
+///
+/// Infinite pdfs stand for techniques that are the only way to find the sample.
+fn mis_weight(pdf: Component, other_pdf: Component) -> Component {
+    if pdf <= 0.0 {
+        return 0.0;
+    }
+    let ratio = other_pdf / pdf;
+    1.0 / (1.0 + ratio * ratio)
+}
+
+/// whether nothing blocks the light's sample, except for the light's own surface
+fn is_visible<E: Scene>(
+    scene: &E,
+    hit: &Hit<E::V>,
+    light: LightId,
+    sample: &LightSample<E::V>,
+    options: &TracingOptions,
+) -> bool {
+    let shadow_ray = hit.next_ray(*sample.direction);
+    // the ray starts a bit off the surface, possibly closer to the light
+    let head_start = (shadow_ray.origin - hit.intersection).dot(sample.direction);
+    let far_clipping = (sample.distance - head_start) * (1.0 - SHADOW_RAY_SHORTENING);
+    if !scene.occluded(shadow_ray, options.near_clipping, far_clipping) {
+        return true;
+    }
+    // rays grazing a light's surface can hit it a bit short of the sampled point due to rounding
+    first_visible_intersection(scene, shadow_ray, options, RayKind::Shadow)
+        .is_some_and(|blocker| blocker.data.light.is_some_and(|(id, _)| id == light))
+}
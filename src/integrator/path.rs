@@ -0,0 +1,90 @@
+use super::*;
+
+/// Follows a single path from the camera through the scene, gathering the light reaching it at every hit.
+///
+/// Without `sample_lights`, this is naive path tracing: light is only found by bouncing into whatever emits it,
+/// so point lights stay dark and small lights make for a lot of noise.
+/// With it, shadow rays are cast to the lights at every hit the material can evaluate,
+/// and the light that bounces would find there is left out so it isn't counted twice.
+pub struct PathTracer {
+    pub sample_lights: bool,
+}
+
+impl<E: Scene> Integrator<E> for PathTracer {
+    fn radiance(&self, scene: &E, mut ray: Ray<E::V>, options: &TracingOptions) -> Color {
+        let mut kind = RayKind::Camera;
+        let mut color = Color::black();
+        let mut alpha = options.background_color.alpha;
+        // how much of the light arriving along the ray reaches the camera
+        let mut throughput = Color::white();
+        // whether the previous hit already gathered light from this direction by sampling the lights,
+        // in which case the emission of lights hit along the ray is left out so it isn't counted twice
+        let mut was_light_sampled = false;
+        // those of the surface the ray leaves, which only sees the lights they include
+        let mut light_links: Option<&LightLinks> = None;
+        let mut bounces = BounceCounts::default();
+
+        loop {
+            let intersection = match first_visible_intersection(scene, ray, options, kind) {
+                Some(intersection) => intersection,
+                None => {
+                    let mut escaped = options.background_color;
+                    if !was_light_sampled {
+                        for (index, light) in scene.lights().iter().enumerate() {
+                            if is_linked(light_links, LightId(index)) {
+                                escaped += light.escaped_radiance(ray.direction);
+                            }
+                        }
+                    }
+                    color += throughput * escaped;
+                    break;
+                }
+            };
+            alpha = 1.0;
+
+            let SurfaceHit {
+                hit,
+                material,
+                light,
+                light_links: hit_light_links,
+                ..
+            } = intersection.data;
+            let behavior = material.behavior(hit);
+            if self.sample_lights {
+                color += throughput
+                    * direct_lighting(scene, &hit, material, hit_light_links, options, false);
+            }
+            let is_counted = match light {
+                Some((id, _)) => !was_light_sampled && is_linked(light_links, id),
+                None => true,
+            };
+            if is_counted {
+                color += throughput * behavior.emission;
+            }
+
+            let next_bounce = match behavior.next_bounce {
+                Some(next_bounce) => next_bounce,
+                None => break,
+            };
+            if !bounces.add(behavior.lobe, options) {
+                break;
+            }
+            throughput *= behavior.color;
+            if !survives_roulette(&mut throughput, &bounces, options) {
+                break;
+            }
+
+            // sampling the lights covers what the material can evaluate, which leaves out specular lobes
+            // even where they're mixed with others that do evaluate in the same direction
+            was_light_sampled = self.sample_lights
+                && !behavior.is_specular
+                && material.evaluate(&hit, next_bounce.direction).brightness() > 0.0;
+            kind = ray_kind(behavior.lobe);
+            light_links = hit_light_links;
+            ray = next_bounce;
+        }
+
+        color.alpha = alpha;
+        color
+    }
+}
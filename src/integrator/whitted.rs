@@ -0,0 +1,67 @@
+use super::*;
+
+/// Classic recursive ray tracing after Whitted: direct light at every hit, only following bounces the lights can't be sampled for.
+///
+/// That's mirrors and clear glass, but not diffuse or glossy surfaces, so there's no indirect light between them.
+/// Fast and nearly noise-free for scenes lit by point and directional lights.
+pub struct WhittedTracer;
+
+impl<E: Scene> Integrator<E> for WhittedTracer {
+    fn radiance(&self, scene: &E, mut ray: Ray<E::V>, options: &TracingOptions) -> Color {
+        let mut kind = RayKind::Camera;
+        let mut color = Color::black();
+        let mut alpha = options.background_color.alpha;
+        let mut throughput = Color::white();
+        let mut light_links: Option<&LightLinks> = None;
+        let mut bounces = BounceCounts::default();
+
+        loop {
+            let intersection = match first_visible_intersection(scene, ray, options, kind) {
+                Some(intersection) => intersection,
+                None => {
+                    let mut escaped = options.background_color;
+                    for (index, light) in scene.lights().iter().enumerate() {
+                        if is_linked(light_links, LightId(index)) {
+                            escaped += light.escaped_radiance(ray.direction);
+                        }
+                    }
+                    color += throughput * escaped;
+                    break;
+                }
+            };
+            alpha = 1.0;
+
+            let SurfaceHit {
+                hit,
+                material,
+                light,
+                light_links: hit_light_links,
+                ..
+            } = intersection.data;
+            let behavior = material.behavior(hit);
+            color += throughput
+                * direct_lighting(scene, &hit, material, hit_light_links, options, false);
+            // only reached from the camera or along specular bounces, which shadow rays don't cover
+            if light.is_none_or(|(id, _)| is_linked(light_links, id)) {
+                color += throughput * behavior.emission;
+            }
+
+            let next_bounce = match behavior.next_bounce {
+                Some(next_bounce) => next_bounce,
+                None => break,
+            };
+            let was_light_sampled =
+                !behavior.is_specular && material.pdf(&hit, next_bounce.direction) > 0.0;
+            if was_light_sampled || !bounces.add(behavior.lobe, options) {
+                break;
+            }
+            throughput *= behavior.color;
+            kind = ray_kind(behavior.lobe);
+            light_links = hit_light_links;
+            ray = next_bounce;
+        }
+
+        color.alpha = alpha;
+        color
+    }
+}
@@ -0,0 +1,207 @@
+use super::*;
+use std::f32::consts;
+
+/// An infinitely small light shining equally in all directions.
+pub struct PointLight<V: Vector> {
+    pub position: V,
+    pub color: Color,
+    /// radiant intensity in watts per steradian, unless there's a photometric profile
+    pub intensity: Component,
+    /// measured intensities to shine with instead of shining equally in all directions
+    pub photometry: Option<Photometry<V>>,
+}
+
+impl<V: Vector> PointLight<V> {
+    /// the radiant intensity in the given direction away from the light
+    pub fn intensity_towards(&self, direction: Normalized<V>) -> Component {
+        self.photometry
+            .as_ref()
+            .map_or(self.intensity, |p| p.intensity(direction))
+    }
+}
+
+impl<V: Vector> Light for PointLight<V> {
+    type V = V;
+
+    fn sample(&self, point: V) -> Option<LightSample<V>> {
+        let offset = self.position - point;
+        let distance = offset.norm();
+        if distance <= 0.0 {
+            return None;
+        }
+        let direction = offset.normalized();
+        let intensity = self.intensity_towards(-direction);
+        if intensity <= 0.0 {
+            return None;
+        }
+        Some(LightSample {
+            direction,
+            distance,
+            radiance: self.color * (intensity / (distance * distance)),
+            pdf: Component::INFINITY,
+        })
+    }
+
+    fn bounds(&self) -> Option<LightBounds<V>> {
+        let power = match &self.photometry {
+            Some(photometry) => photometry.power(|_| 1.0),
+            None => 2.0 * consts::TAU * self.intensity,
+        } * self.color.luminance();
+        Some(LightBounds::omnidirectional(
+            BoundingBox::around_point(self.position),
+            power,
+        ))
+    }
+}
+
+/// A point light that only shines within a cone, fading out towards its edge.
+pub struct SpotLight<V: Vector> {
+    pub position: V,
+    /// the axis of the cone
+    pub direction: Normalized<V>,
+    pub color: Color,
+    /// radiant intensity along the axis, in watts per steradian, unless there's a photometric profile
+    pub intensity: Component,
+    /// the angle between the axis and the edge of the cone, in radians
+    pub angle: Component,
+    /// the fraction of the cone's angle over which it fades out, in `0.0..=1.0`; 0 for a hard edge
+    pub softness: Component,
+    /// measured intensities to shine with instead of `intensity`, still limited to the cone
+    pub photometry: Option<Photometry<V>>,
+}
+
+impl<V: Vector> SpotLight<V> {
+    /// the radiant intensity in the given direction away from the light
+    pub fn intensity_towards(&self, direction: Normalized<V>) -> Component {
+        let intensity = self
+            .photometry
+            .as_ref()
+            .map_or(self.intensity, |p| p.intensity(direction));
+        intensity * self.falloff(direction)
+    }
+
+    /// how much of the intensity the cone lets through in the given direction away from the light
+    pub fn falloff(&self, direction: Normalized<V>) -> Component {
+        let cos = self.direction.dot(direction);
+        let cos_outer = self.angle.cos();
+        let cos_inner = (self.angle * (1.0 - self.softness.clamp(0.0, 1.0))).cos();
+        if cos >= cos_inner {
+            1.0
+        } else if cos <= cos_outer {
+            0.0
+        } else {
+            let t = (cos - cos_outer) / (cos_inner - cos_outer);
+            t * t * (3.0 - 2.0 * t)
+        }
+    }
+}
+
+impl<V: Vector> Light for SpotLight<V> {
+    type V = V;
+
+    fn sample(&self, point: V) -> Option<LightSample<V>> {
+        let offset = self.position - point;
+        let distance = offset.norm();
+        if distance <= 0.0 {
+            return None;
+        }
+        let direction = offset.normalized();
+        let intensity = self.intensity_towards(-direction);
+        if intensity <= 0.0 {
+            return None;
+        }
+        Some(LightSample {
+            direction,
+            distance,
+            radiance: self.color * (intensity / (distance * distance)),
+            pdf: Component::INFINITY,
+        })
+    }
+
+    fn bounds(&self) -> Option<LightBounds<V>> {
+        let inner_angle = self.angle * (1.0 - self.softness.clamp(0.0, 1.0));
+        let power = match &self.photometry {
+            Some(photometry) => photometry.power(|direction| self.falloff(direction)),
+            // the power within the fully lit inner cone, plus the fading edge as if it were lit fully
+            None => consts::TAU * (1.0 - self.angle.cos()) * self.intensity,
+        };
+        Some(LightBounds {
+            bounds: BoundingBox::around_point(self.position),
+            power: power * self.color.luminance(),
+            axis: self.direction,
+            cos_normals: inner_angle.cos(),
+            cos_emission: (self.angle - inner_angle).cos(),
+            is_two_sided: false,
+        })
+    }
+}
+
+/// A light infinitely far away, like the sun, whose rays all arrive from (nearly) the same direction.
+pub struct DirectionalLight {
+    /// the direction the light travels in, i.e. pointing away from the sun
+    pub direction: Normalized<Vec3>,
+    pub color: Color,
+    /// irradiance on a surface facing the light, in watts per square meter
+    pub irradiance: Component,
+    /// the apparent size of the light's disk in radians, which softens shadows; 0 for perfectly sharp ones
+    pub angular_diameter: Component,
+}
+
+impl DirectionalLight {
+    /// the sun's disk is about half a degree across as seen from earth
+    pub const SUN_ANGULAR_DIAMETER: Component = 0.0093;
+
+    /// whether the direction points into the disk, which is never the case for perfectly sharp lights
+    fn is_within_disk(&self, direction: Normalized<Vec3>) -> bool {
+        self.angular_diameter > 0.0
+            && (-self.direction).dot(direction) >= (self.angular_diameter / 2.0).cos()
+    }
+
+    fn disk_solid_angle(&self) -> Component {
+        // 1 - cos, without losing precision for small disks
+        consts::TAU * 2.0 * (self.angular_diameter / 4.0).sin().powi(2)
+    }
+}
+
+impl Light for DirectionalLight {
+    type V = Vec3;
+
+    fn sample(&self, _point: Vec3) -> Option<LightSample<Vec3>> {
+        let towards_light = -self.direction;
+        let direction = if self.angular_diameter > 0.0 {
+            // uniformly within the cone covered by the disk
+            let cone = uniform_cone((self.angular_diameter / 2.0).cos());
+            let frame = Frame::new(towards_light, *Vec3::positive_x());
+            frame.to_world(cone).normalized()
+        } else {
+            towards_light
+        };
+        Some(LightSample {
+            direction,
+            distance: Component::INFINITY,
+            // integrating the disk's radiance over its solid angle gives the irradiance
+            radiance: self.color * self.irradiance,
+            pdf: if self.angular_diameter > 0.0 {
+                1.0 / self.disk_solid_angle()
+            } else {
+                Component::INFINITY
+            },
+        })
+    }
+
+    /// the disk itself, as seen in reflections or by the camera
+    fn escaped_radiance(&self, direction: Normalized<Vec3>) -> Color {
+        if !self.is_within_disk(direction) {
+            return Color::black();
+        }
+        self.color * (self.irradiance / self.disk_solid_angle())
+    }
+
+    fn pdf(&self, _point: Vec3, direction: Normalized<Vec3>) -> Component {
+        if self.is_within_disk(direction) {
+            1.0 / self.disk_solid_angle()
+        } else {
+            0.0
+        }
+    }
+}
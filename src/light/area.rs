@@ -0,0 +1,169 @@
+use super::*;
+use std::f32::consts;
+
+/// how many points on the surface to average the emission color over, for estimating how much light it gives
+const AVERAGE_COLOR_SAMPLES: usize = 64;
+
+/// A shape that emits light, which rays can hit like any other element but which can also be sampled like a light.
+///
+/// It has to be added to a scene with `Scene::add_area_light`, so it's known both as an element and a light
+/// and hits on it can tell which light they found; otherwise the light arriving from it directly would be counted twice.
+pub struct AreaLight<S, T = Color> {
+    shape: S,
+    /// with the strength worked out for the shape's area
+    emission: EmissiveMaterial<T>,
+    /// the emission color averaged over the surface
+    average_color: Color,
+}
+
+impl<S: SampleableShape, T: Texture<S::V>> AreaLight<S, T> {
+    /// Spreads `Watts` and `Lumens` over the shape's area; shapes without any give no light.
+    pub fn new(shape: S, emission: EmissiveMaterial<T>) -> Self {
+        let area = shape.area();
+        let radiance = if area > 0.0 {
+            emission.radiance_over(area)
+        } else {
+            0.0
+        };
+        let mut light = Self {
+            shape,
+            emission: EmissiveMaterial {
+                strength: EmissionStrength::Radiance(radiance),
+                ..emission
+            },
+            average_color: Color::black(),
+        };
+        if area > 0.0 {
+            light.average_color = light.estimate_average_color();
+        }
+        light
+    }
+
+    /// the emission color where the ray first hits the shape
+    fn color_along(&self, ray: Ray<S::V>) -> Option<Color> {
+        count_intersection_tests(1);
+        let hit = self.shape.first_intersection(&ray, 0.0)?.data;
+        Some(self.emission.color.color_at(&hit))
+    }
+
+    /// looks at points picked on the surface from just in front of them
+    fn estimate_average_color(&self) -> Color {
+        let offset = 1e-3 * self.shape.bounds().map_or(1.0, |b| b.extent().norm());
+        let mut sum = Color::black();
+        let mut count = 0;
+        for _ in 0..AVERAGE_COLOR_SAMPLES {
+            let sample = self.shape.sample_area();
+            let ray = Ray {
+                origin: sample.position + sample.normal * offset,
+                direction: -sample.normal,
+            };
+            if let Some(color) = self.color_along(ray) {
+                sum += color;
+                count += 1;
+            }
+        }
+        if count == 0 {
+            return Color::white(); // rounding got in the way, so better overestimate than leave the light out
+        }
+        sum / count as Component
+    }
+}
+
+impl<S: SampleableShape, T: Texture<S::V>> Light for AreaLight<S, T> {
+    type V = S::V;
+
+    fn sample(&self, point: S::V) -> Option<LightSample<S::V>> {
+        if self.shape.area() <= 0.0 {
+            return None; // nothing to pick a point on, e.g. an empty mesh
+        }
+        let sample = self.shape.sample_from(point)?;
+        let offset = sample.position - point;
+        let distance = offset.norm();
+        if distance <= 0.0 || sample.pdf <= 0.0 {
+            return None;
+        }
+        let direction = offset.normalized();
+        if !self.emission.is_two_sided && sample.normal.dot(direction) >= 0.0 {
+            return None; // looking at the back
+        }
+        // the point that's seen in the direction, like `pdf` assumes
+        let color = self.color_along(Ray {
+            origin: point,
+            direction,
+        })?;
+        Some(LightSample {
+            direction,
+            distance,
+            radiance: color * (self.emission.radiance() / sample.pdf),
+            pdf: sample.pdf,
+        })
+    }
+
+    fn pdf(&self, point: S::V, direction: Normalized<S::V>) -> Component {
+        if self.shape.area() <= 0.0 {
+            return 0.0;
+        }
+        self.shape.pdf_from(point, direction)
+    }
+
+    fn bounds(&self) -> Option<LightBounds<S::V>> {
+        let bounds = self.shape.bounds()?;
+        let sides = if self.emission.is_two_sided { 2.0 } else { 1.0 };
+        // a diffuse emitter with radiance L emits π L per unit area on each side
+        let power = consts::PI
+            * sides
+            * self.shape.area()
+            * self.emission.radiance()
+            * self.average_color.luminance();
+        let light_bounds = LightBounds::omnidirectional(bounds, power);
+        Some(match self.shape.flat_normal() {
+            Some(normal) => LightBounds {
+                axis: normal,
+                cos_normals: 1.0,
+                is_two_sided: self.emission.is_two_sided,
+                ..light_bounds
+            },
+            None => light_bounds,
+        })
+    }
+}
+
+impl<S: SampleableShape, T: Texture<S::V>> SceneElement for AreaLight<S, T> {
+    type V = S::V;
+
+    fn first_intersection(
+        &self,
+        ray: Ray<Self::V>,
+        near_clipping: Component,
+    ) -> ElementIntersection<'_, Self::V> {
+        count_intersection_tests(1);
+        self.shape
+            .first_intersection(&ray, near_clipping)
+            .map(|i| Intersection {
+                distance: i.distance,
+                data: SurfaceHit {
+                    hit: i.data,
+                    material: &self.emission,
+                    light: None,
+                    visibility: Visibility::default(),
+                    light_links: None,
+                    element: ElementId::default(),
+                    material_id: MaterialId::default(),
+                },
+            })
+    }
+
+    fn occluded(
+        &self,
+        ray: Ray<Self::V>,
+        near_clipping: Component,
+        far_clipping: Component,
+    ) -> bool {
+        count_intersection_tests(1);
+        self.shape.occluded(&ray, near_clipping, far_clipping)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox<Self::V>> {
+        self.shape.bounds()
+    }
+}
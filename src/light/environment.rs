@@ -0,0 +1,136 @@
+use super::*;
+use rand::*;
+use std::error::Error;
+use std::f32::consts;
+use std::path::Path;
+
+/// Light arriving from infinitely far away in every direction, looked up from an equirectangular (latitude-longitude) image.
+///
+/// The center of the image lies towards positive z, with its top edge straight up.
+/// Directions are importance sampled according to the image's luminance, so bright spots like the sun are found quickly.
+pub struct EnvironmentMap {
+    image: Image,
+    /// picks pixels in proportion to the light arriving from them
+    distribution: Distribution2D,
+    /// rotation around the vertical axis in radians, turning what's in front (towards positive z) towards positive x
+    pub rotation: Component,
+    /// scales the image's values
+    pub intensity: Component,
+}
+
+impl EnvironmentMap {
+    pub fn new(image: Image) -> Self {
+        let (width, height) = (image.width(), image.height());
+        let weights: Vec<_> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                // rows near the poles cover less of the sphere
+                let polar = (y as Component + 0.5) / height as Component * consts::PI;
+                image.pixel(x, y).luminance().max(0.0) * polar.sin()
+            })
+            .collect();
+        Self {
+            distribution: Distribution2D::new(&weights, width),
+            image,
+            rotation: 0.0,
+            intensity: 1.0,
+        }
+    }
+
+    /// loads OpenEXR files by their `.exr` extension and anything else as Radiance HDR
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let is_exr = path
+            .as_ref()
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("exr"));
+        let image = if is_exr {
+            Image::open_exr(path)?
+        } else {
+            Image::open_hdr(path)?
+        };
+        Ok(Self::new(image))
+    }
+
+    /// position in the image, from the top left, as fractions of its size
+    fn image_position(&self, direction: Vec3) -> (Component, Component) {
+        let azimuth = direction.x.atan2(direction.z) - self.rotation;
+        let polar = direction.y.clamp(-1.0, 1.0).acos();
+        let x = (azimuth / consts::TAU + 0.5).rem_euclid(1.0);
+        (x, polar / consts::PI)
+    }
+
+    /// the inverse of `image_position`
+    fn direction(&self, x: Component, y: Component) -> Normalized<Vec3> {
+        let azimuth = (x - 0.5) * consts::TAU + self.rotation;
+        let polar = y * consts::PI;
+        Vec3::new(
+            polar.sin() * azimuth.sin(),
+            polar.cos(),
+            polar.sin() * azimuth.cos(),
+        )
+        .normalized()
+    }
+
+    fn pixel_at(&self, x: Component, y: Component) -> (usize, usize) {
+        let column = ((x * self.image.width() as Component) as usize).min(self.image.width() - 1);
+        let row = ((y * self.image.height() as Component) as usize).min(self.image.height() - 1);
+        (column, row)
+    }
+
+    /// the light arriving from the given direction, taken from the pixel it falls into without filtering to match the sampling
+    pub fn radiance(&self, direction: Normalized<Vec3>) -> Color {
+        let (x, y) = self.image_position(*direction);
+        let (column, row) = self.pixel_at(x, y);
+        let mut color = self.image.pixel(column, row) * self.intensity;
+        color.alpha = 1.0;
+        color
+    }
+
+    /// the pdf per unit solid angle of sampling the given direction
+    pub fn pdf(&self, direction: Normalized<Vec3>) -> Component {
+        let (x, y) = self.image_position(*direction);
+        let (column, row) = self.pixel_at(x, y);
+        self.solid_angle_pdf(self.distribution.probability(column, row), y)
+    }
+
+    /// converts the probability of picking a pixel to a pdf per unit solid angle, for a point at the given height in the image
+    fn solid_angle_pdf(&self, probability: Component, y: Component) -> Component {
+        let sin_polar = (y * consts::PI).sin();
+        if sin_polar <= 0.0 {
+            return 0.0;
+        }
+        // each pixel spans 2π / width by π / height radians, shrinking horizontally towards the poles
+        let pixel_count = (self.image.width() * self.image.height()) as Component;
+        probability * pixel_count / (2.0 * consts::PI * consts::PI * sin_polar)
+    }
+}
+
+impl Light for EnvironmentMap {
+    type V = Vec3;
+
+    fn sample(&self, _point: Vec3) -> Option<LightSample<Vec3>> {
+        let (column, row, probability) = self.distribution.sample();
+        let mut rng = thread_rng();
+        let x = (column as Component + rng.gen::<Component>()) / self.image.width() as Component;
+        let y = (row as Component + rng.gen::<Component>()) / self.image.height() as Component;
+        let pdf = self.solid_angle_pdf(probability, y);
+        if pdf <= 0.0 {
+            return None;
+        }
+        let direction = self.direction(x, y);
+        Some(LightSample {
+            direction,
+            distance: Component::INFINITY,
+            radiance: self.image.pixel(column, row) * (self.intensity / pdf),
+            pdf,
+        })
+    }
+
+    fn escaped_radiance(&self, direction: Normalized<Vec3>) -> Color {
+        self.radiance(direction)
+    }
+
+    fn pdf(&self, _point: Vec3, direction: Normalized<Vec3>) -> Component {
+        EnvironmentMap::pdf(self, direction)
+    }
+}
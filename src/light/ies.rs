@@ -0,0 +1,320 @@
+use super::*;
+use std::error::Error;
+use std::f32::consts;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// A fixture's measured luminous intensity by direction, as read from an IES LM-63 photometric file.
+///
+/// Only type C photometry is supported, which is what nearly all architectural fixtures use:
+/// vertical angles go from the nadir (straight down) at 0° to straight up at 180°,
+/// and horizontal angles go around the vertical axis.
+#[derive(Debug, Clone)]
+pub struct IesProfile {
+    /// in degrees, ascending
+    vertical_angles: Vec<Component>,
+    /// in degrees, ascending
+    horizontal_angles: Vec<Component>,
+    /// in candela, with all vertical angles for the first horizontal angle, then for the second and so on
+    candela: Vec<Component>,
+}
+
+impl IesProfile {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Box<dyn Error>> {
+        // the header is free-form keyword lines, ending with the tilt line that the numbers follow
+        let mut lines = text.lines();
+        let tilt = loop {
+            let line = lines.next().ok_or("missing TILT line")?.trim();
+            if let Some(tilt) = line.strip_prefix("TILT=") {
+                break tilt.trim();
+            }
+        };
+        let mut numbers = lines
+            .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ','))
+            .filter(|token| !token.is_empty())
+            .map(|token| token.parse::<Component>());
+        let mut next = || -> Result<Component, Box<dyn Error>> {
+            Ok(numbers.next().ok_or("unexpected end of file")??)
+        };
+
+        if tilt == "INCLUDE" {
+            // skip the lamp-to-luminaire geometry and the angles and factors that follow it
+            next()?;
+            let count = next()? as usize;
+            for _ in 0..2 * count {
+                next()?;
+            }
+        }
+
+        let _lamp_count = next()?;
+        let _lumens_per_lamp = next()?;
+        let multiplier = next()?;
+        let vertical_count = next()? as usize;
+        let horizontal_count = next()? as usize;
+        let photometric_type = next()?;
+        let _units = next()?;
+        let _dimensions = [next()?, next()?, next()?];
+        let ballast_factor = next()?;
+        let _ballast_lamp_factor = next()?;
+        let _input_watts = next()?;
+
+        if photometric_type != 1.0 {
+            return Err("only type C photometry is supported".into());
+        }
+        if vertical_count == 0 || horizontal_count == 0 {
+            return Err("no angles".into());
+        }
+
+        let mut read = |count: usize| (0..count).map(|_| next()).collect::<Result<Vec<_>, _>>();
+        let vertical_angles = read(vertical_count)?;
+        let horizontal_angles = read(horizontal_count)?;
+        let candela: Vec<_> = read(vertical_count * horizontal_count)?
+            .into_iter()
+            .map(|c| c * multiplier * ballast_factor)
+            .collect();
+
+        let is_ascending = |angles: &[Component]| angles.windows(2).all(|w| w[0] < w[1]);
+        if !is_ascending(&vertical_angles) || !is_ascending(&horizontal_angles) {
+            return Err("angles must be ascending".into());
+        }
+
+        Ok(Self {
+            vertical_angles,
+            horizontal_angles,
+            candela,
+        })
+    }
+
+    /// Luminous intensity in candela, interpolated between the measured angles, which are in radians here.
+    ///
+    /// Horizontal angles the file leaves out are filled in by its symmetry.
+    pub fn candela(&self, vertical: Component, horizontal: Component) -> Component {
+        let vertical = vertical.to_degrees();
+        let horizontal = self.fold_horizontal(horizontal.to_degrees().rem_euclid(360.0));
+
+        let vertical_count = self.vertical_angles.len();
+        let value = |h: usize, v: usize| self.candela[h * vertical_count + v];
+        let Some((v0, v1, vt)) = interpolation(&self.vertical_angles, vertical, false) else {
+            return 0.0; // not measured, e.g. above a downlight
+        };
+        let (h0, h1, ht) =
+            interpolation(&self.horizontal_angles, horizontal, true).unwrap_or((0, 0, 0.0));
+
+        let lerp = |a: Component, b: Component, t: Component| a + (b - a) * t;
+        lerp(
+            lerp(value(h0, v0), value(h0, v1), vt),
+            lerp(value(h1, v0), value(h1, v1), vt),
+            ht,
+        )
+    }
+
+    /// maps the angle into the range the file covers, which tells its symmetry
+    fn fold_horizontal(&self, angle: Component) -> Component {
+        let first = self.horizontal_angles[0];
+        let last = *self.horizontal_angles.last().unwrap();
+        if first == 0.0 && last == 90.0 {
+            // the same in each quadrant
+            let angle = if angle > 180.0 { 360.0 - angle } else { angle };
+            if angle > 90.0 {
+                180.0 - angle
+            } else {
+                angle
+            }
+        } else if first == 0.0 && last == 180.0 {
+            // mirrored across the 0-180 plane
+            if angle > 180.0 {
+                360.0 - angle
+            } else {
+                angle
+            }
+        } else if first == 90.0 && last == 270.0 {
+            // mirrored across the 90-270 plane
+            if angle < 90.0 {
+                180.0 - angle
+            } else if angle > 270.0 {
+                540.0 - angle
+            } else {
+                angle
+            }
+        } else {
+            angle
+        }
+    }
+}
+
+/// The indices of the angles around the given one and how far it is between them.
+///
+/// `None` outside the range unless `wraps`, in which case the last angle leads back to the first.
+fn interpolation(
+    angles: &[Component],
+    angle: Component,
+    wraps: bool,
+) -> Option<(usize, usize, Component)> {
+    let last = angles.len() - 1;
+    if angles.len() == 1 {
+        return Some((0, 0, 0.0));
+    }
+    if angle < angles[0] || angle > angles[last] {
+        if !wraps {
+            return None;
+        }
+        // between the last angle and the first one, a full turn later
+        let span = angles[0] + 360.0 - angles[last];
+        let offset = (angle - angles[last]).rem_euclid(360.0);
+        let t = if span > 0.0 { offset / span } else { 0.0 };
+        return Some((last, 0, t.clamp(0.0, 1.0)));
+    }
+    let upper = angles.partition_point(|&a| a <= angle).clamp(1, last);
+    let lower = upper - 1;
+    let t = (angle - angles[lower]) / (angles[upper] - angles[lower]);
+    Some((lower, upper, t.clamp(0.0, 1.0)))
+}
+
+/// how many steps to take across the vertical angles when integrating a profile over all directions,
+/// with twice as many around the horizontal ones
+const POWER_STEPS: usize = 180;
+
+/// An `IesProfile` placed in the scene, to shape the light of a point or spot light.
+///
+/// The light shines with the measured intensities in place of its own `intensity`.
+pub struct Photometry<V: Vector> {
+    pub profile: Arc<IesProfile>,
+    /// the photometric nadir, i.e. the direction of vertical angle 0
+    pub down: Normalized<V>,
+    /// the direction of horizontal angle 0, perpendicular to `down`
+    pub front: Normalized<V>,
+    /// the direction of horizontal angle 90, perpendicular to the other two
+    pub side: Normalized<V>,
+    /// scales the measured intensities, e.g. to dim the fixture; 1 keeps them as they are
+    pub multiplier: Component,
+}
+
+impl<V: Vector> Photometry<V> {
+    /// the radiant intensity in the given direction away from the light, in watts per steradian
+    pub fn intensity(&self, direction: Normalized<V>) -> Component {
+        let vertical = self.down.dot(direction).clamp(-1.0, 1.0).acos();
+        let horizontal = self.side.dot(direction).atan2(self.front.dot(direction));
+        self.intensity_at(vertical, horizontal)
+    }
+
+    /// The radiant flux in watts, integrating the intensity over all directions
+    /// scaled by `weight` in each, e.g. to leave out what a spot light's cone cuts off.
+    pub fn power<W: Fn(Normalized<V>) -> Component>(&self, weight: W) -> Component {
+        let vertical_step = consts::PI / POWER_STEPS as Component;
+        let horizontal_step = consts::TAU / (2 * POWER_STEPS) as Component;
+        let mut power = 0.0;
+        for i in 0..POWER_STEPS {
+            let vertical = (i as Component + 0.5) * vertical_step;
+            let (sin_vertical, cos_vertical) = vertical.sin_cos();
+            for j in 0..2 * POWER_STEPS {
+                let horizontal = (j as Component + 0.5) * horizontal_step;
+                let around = *self.front * horizontal.cos() + *self.side * horizontal.sin();
+                let direction = (*self.down * cos_vertical + around * sin_vertical).normalized();
+                // the solid angle of each step shrinks towards the poles
+                power += self.intensity_at(vertical, horizontal) * weight(direction) * sin_vertical;
+            }
+        }
+        power * vertical_step * horizontal_step
+    }
+
+    fn intensity_at(&self, vertical: Component, horizontal: Component) -> Component {
+        self.profile.candela(vertical, horizontal) * self.multiplier / LUMENS_PER_WATT
+    }
+}
+
+impl Photometry<Vec3> {
+    /// Horizontal angles go counterclockwise as seen from above, as in the file.
+    pub fn new(profile: Arc<IesProfile>, down: Normalized<Vec3>, front: Vec3) -> Self {
+        // reuse the frame's projection to make front perpendicular to down
+        let frame = Frame::new(down, front);
+        Self {
+            profile,
+            down,
+            front: frame.tangent,
+            // with x right, y up and z forward, counterclockwise from above turns z towards -x
+            side: frame.tangent.cross(-down),
+            multiplier: 1.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a downlight, the same in each quadrant and brighter towards 90° horizontally
+    const DOWNLIGHT: &str = "IESNA:LM-63-2002
+[TEST] downlight
+TILT=NONE
+1 1000 2 3 2 1 2 0.1 0.1 0
+1.0 1.0 10
+0 45 90
+0 90
+100 50 0
+300, 150, 0
+";
+
+    fn assert_close(actual: Component, expected: Component) {
+        assert!(
+            (actual - expected).abs() < 1e-3,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn parses_measured_intensities_with_the_multiplier() {
+        let profile = IesProfile::parse(DOWNLIGHT).unwrap();
+        assert_close(profile.candela(0.0, 0.0), 200.0);
+        assert_close(profile.candela(45f32.to_radians(), 0.0), 100.0);
+        assert_close(profile.candela(0.0, 90f32.to_radians()), 600.0);
+    }
+
+    #[test]
+    fn interpolates_between_measured_angles() {
+        let profile = IesProfile::parse(DOWNLIGHT).unwrap();
+        assert_close(profile.candela(22.5f32.to_radians(), 0.0), 150.0);
+        assert_close(profile.candela(0.0, 45f32.to_radians()), 400.0);
+        assert_close(
+            profile.candela(22.5f32.to_radians(), 45f32.to_radians()),
+            300.0,
+        );
+    }
+
+    #[test]
+    fn fills_in_horizontal_angles_by_symmetry() {
+        let profile = IesProfile::parse(DOWNLIGHT).unwrap();
+        for &(horizontal, folded) in &[(180.0, 0.0), (270.0, 90.0), (135.0, 45.0), (-30.0, 30.0)] {
+            let candela = |angle: Component| profile.candela(0.2, angle.to_radians());
+            assert_close(candela(horizontal), candela(folded));
+        }
+    }
+
+    #[test]
+    fn gives_no_light_outside_the_measured_vertical_angles() {
+        let profile = IesProfile::parse(DOWNLIGHT).unwrap();
+        assert_eq!(profile.candela(120f32.to_radians(), 0.0), 0.0);
+    }
+
+    #[test]
+    fn skips_included_tilt_data() {
+        let text = DOWNLIGHT.replace("TILT=NONE", "TILT=INCLUDE\n1\n2\n0 90\n1 1");
+        let profile = IesProfile::parse(&text).unwrap();
+        assert_close(profile.candela(0.0, 0.0), 200.0);
+    }
+
+    #[test]
+    fn rejects_unsupported_or_broken_files() {
+        assert!(IesProfile::parse("IESNA:LM-63-2002\n1 1000 1").is_err());
+        // type B photometry
+        assert!(IesProfile::parse(&DOWNLIGHT.replace("1 1000 2 3 2 1", "1 1000 2 3 2 2")).is_err());
+        assert!(IesProfile::parse(&DOWNLIGHT.replace("0 45 90", "0 90 45")).is_err());
+        assert!(IesProfile::parse(&DOWNLIGHT.replace("300, 150, 0", "300, 150")).is_err());
+    }
+}
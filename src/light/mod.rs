@@ -0,0 +1,81 @@
+mod analytic;
+mod area;
+mod environment;
+mod ies;
+mod selection;
+mod sky;
+
+pub use analytic::*;
+pub use area::*;
+pub use environment::*;
+pub use ies::*;
+pub use selection::*;
+pub use sky::*;
+
+use super::*;
+use std::sync::Arc;
+
+/// A direction from which a light illuminates some point.
+pub struct LightSample<V: Vector> {
+    /// from the lit point towards the light
+    pub direction: Normalized<V>,
+    /// how far away the light is along `direction`, for checking whether it's occluded; infinite for lights at infinity
+    pub distance: Component,
+    /// the light arriving from `direction`, already divided by the probability of sampling it
+    pub radiance: Color,
+    /// the probability density of sampling `direction`, per unit solid angle;
+    /// infinite for lights shining from a single direction, like point lights
+    pub pdf: Component,
+}
+
+/// Identifies a light added to a scene, e.g. to link it to the elements it illuminates.
+///
+/// Handed out by `Scene::add_light` as the light's index in `Scene::lights`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct LightId(pub usize);
+
+/// A light source that isn't part of the scene's geometry, so rays can't hit it and it has to be sampled explicitly.
+pub trait Light: 'static + Send + Sync {
+    type V: Vector;
+
+    /// `None` if the light can't reach the point at all
+    fn sample(&self, point: Self::V) -> Option<LightSample<Self::V>>;
+
+    /// the light arriving along rays that leave the scene in the given direction, for lights surrounding it
+    fn escaped_radiance(&self, _direction: Normalized<Self::V>) -> Color {
+        Color::black()
+    }
+
+    /// The pdf per unit solid angle of `sample` picking `direction` from the point,
+    /// for weighing it against finding the light by other means, like bouncing off a surface.
+    ///
+    /// 0 for lights that rays can't find, like point lights.
+    fn pdf(&self, _point: Self::V, _direction: Normalized<Self::V>) -> Component {
+        0.0
+    }
+
+    /// where the light is and how much it emits, for picking among many lights; `None` for lights surrounding the scene
+    fn bounds(&self) -> Option<LightBounds<Self::V>> {
+        None
+    }
+}
+
+impl<L: Light + ?Sized> Light for Arc<L> {
+    type V = L::V;
+
+    fn sample(&self, point: Self::V) -> Option<LightSample<Self::V>> {
+        (**self).sample(point)
+    }
+
+    fn escaped_radiance(&self, direction: Normalized<Self::V>) -> Color {
+        (**self).escaped_radiance(direction)
+    }
+
+    fn pdf(&self, point: Self::V, direction: Normalized<Self::V>) -> Component {
+        (**self).pdf(point, direction)
+    }
+
+    fn bounds(&self) -> Option<LightBounds<Self::V>> {
+        (**self).bounds()
+    }
+}
@@ -0,0 +1,516 @@
+use super::*;
+use rand::*;
+use std::f32::consts;
+
+/// How direct lighting picks which lights to cast shadow rays towards.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LightSelection {
+    /// every light at every hit, which is best for a handful of lights
+    All,
+    /// a single light, in proportion to how much power it emits
+    Power,
+    /// a single light, in proportion to an estimate of how much it contributes at the hit, found with a `LightSampler`'s tree
+    Tree,
+}
+
+/// Where a light is and which way it shines, conservatively, for estimating how much it contributes at a point.
+///
+/// The light leaves surfaces whose normals lie within a cone around `axis`,
+/// spreading out up to a further angle beyond them.
+#[derive(Debug, Copy, Clone)]
+pub struct LightBounds<V: Vector> {
+    pub bounds: BoundingBox<V>,
+    /// total emitted power, weighing the colors by luminance
+    pub power: Component,
+    pub axis: Normalized<V>,
+    /// the cosine of the angle around `axis` containing all the normals, -1 if they go in all directions
+    pub cos_normals: Component,
+    /// the cosine of the angle beyond the normals that light leaves at, 0 for diffuse emitters
+    pub cos_emission: Component,
+    /// whether light also leaves opposite the normals
+    pub is_two_sided: bool,
+}
+
+impl<V: Vector> LightBounds<V> {
+    /// bounds for a light shining equally in every direction
+    pub fn omnidirectional(bounds: BoundingBox<V>, power: Component) -> Self {
+        Self {
+            bounds,
+            power,
+            axis: V::uniform(1.0).normalized(),
+            cos_normals: -1.0,
+            cos_emission: 0.0,
+            is_two_sided: false,
+        }
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let (axis, cos_normals) = cone_union(
+            (self.axis, self.cos_normals),
+            (other.axis, other.cos_normals),
+        );
+        Self {
+            bounds: self.bounds.union(&other.bounds),
+            power: self.power + other.power,
+            axis,
+            cos_normals,
+            cos_emission: self.cos_emission.min(other.cos_emission),
+            is_two_sided: self.is_two_sided || other.is_two_sided,
+        }
+    }
+
+    /// A rough upper bound on the light arriving at the point, following Conty and Kulla's many-light sampling.
+    ///
+    /// `normal` accounts for the light arriving at a grazing angle on the surface; either side counts,
+    /// since light may be transmitted.
+    pub fn importance(&self, point: V, normal: Normalized<V>) -> Component {
+        let center = self.bounds.center();
+        let radius_sq = self.bounds.extent().squared_sum() / 4.0;
+        // keep points inside the bounds from blowing up the estimate
+        let distance_sq = (point - center)
+            .squared_sum()
+            .max(radius_sq)
+            .max(Component::EPSILON);
+
+        let towards_point = (point - center).normalized();
+        let mut cos_towards = self.axis.dot(towards_point);
+        if self.is_two_sided {
+            cos_towards = cos_towards.abs();
+        }
+
+        // the angle the bounds cover as seen from the point, as a sphere around them
+        let cos_bounds = if (point - center).squared_sum() < radius_sq {
+            -1.0
+        } else {
+            (1.0 - radius_sq / distance_sq).max(0.0).sqrt()
+        };
+
+        // the smallest possible angle between a normal and the direction towards the point
+        let cos_outside_normals = cos_difference(cos_towards, self.cos_normals);
+        let cos_closest = cos_difference(cos_outside_normals, cos_bounds);
+        if cos_closest <= self.cos_emission {
+            return 0.0;
+        }
+
+        let cos_incident = normal.dot(towards_point).abs();
+        let cos_incident = cos_difference(cos_incident, cos_bounds);
+        (self.power * cos_closest * cos_incident / distance_sq).max(0.0)
+    }
+}
+
+fn sin_from_cos(cos: Component) -> Component {
+    (1.0 - cos * cos).max(0.0).sqrt()
+}
+
+/// the cosine of `a - b`, or 1 if that's negative
+fn cos_difference(cos_a: Component, cos_b: Component) -> Component {
+    if cos_a > cos_b {
+        1.0
+    } else {
+        cos_a * cos_b + sin_from_cos(cos_a) * sin_from_cos(cos_b)
+    }
+}
+
+/// the smallest cone containing both cones, each given by its axis and the cosine of its angle
+fn cone_union<V: Vector>(
+    (axis_a, cos_a): (Normalized<V>, Component),
+    (axis_b, cos_b): (Normalized<V>, Component),
+) -> (Normalized<V>, Component) {
+    let (angle_a, angle_b) = (cos_a.clamp(-1.0, 1.0).acos(), cos_b.clamp(-1.0, 1.0).acos());
+    let between = axis_a.dot(axis_b).clamp(-1.0, 1.0).acos();
+    if (between + angle_b).min(consts::PI) <= angle_a {
+        return (axis_a, cos_a);
+    }
+    if (between + angle_a).min(consts::PI) <= angle_b {
+        return (axis_b, cos_b);
+    }
+
+    let angle = (angle_a + between + angle_b) / 2.0;
+    if angle >= consts::PI || between.sin() < 1e-6 {
+        return (axis_a, -1.0);
+    }
+    // turn the first axis towards the second by spherical interpolation
+    let turn = angle - angle_a;
+    let axis = (*axis_a * (between - turn).sin() + *axis_b * turn.sin()) / between.sin();
+    (axis.normalized(), angle.cos())
+}
+
+enum Node<V: Vector> {
+    Leaf {
+        bounds: LightBounds<V>,
+        light: usize,
+    },
+    Branch {
+        bounds: LightBounds<V>,
+        // the left child always directly follows its parent
+        right: usize,
+    },
+}
+
+impl<V: Vector> Node<V> {
+    fn bounds(&self) -> &LightBounds<V> {
+        match self {
+            Node::Leaf { bounds, .. } | Node::Branch { bounds, .. } => bounds,
+        }
+    }
+}
+
+/// where a light can be found among those a `LightSampler` picks from
+enum Entry {
+    /// gives no light, so it's never picked
+    Unlit,
+    Unbounded,
+    Bounded {
+        /// the index into `power`
+        power: usize,
+        leaf: usize,
+    },
+}
+
+/// Picks one of a scene's lights at a time, for scenes with too many to sample them all at every hit.
+///
+/// Lights with `LightBounds` are picked by power or with a tree over their bounds, built like `BvhScene`.
+/// The others, like environment maps, get the same chance as all the bounded lights together.
+pub struct LightSampler<V: Vector> {
+    unbounded: Vec<usize>,
+    /// indices of the bounded lights, in the order of `power`
+    bounded: Vec<usize>,
+    power: Option<Distribution1D>,
+    nodes: Vec<Node<V>>,
+    /// the branch each node hangs from, with the root pointing to itself
+    parents: Vec<usize>,
+    /// one for each light, indexed by its id
+    entries: Vec<Entry>,
+}
+
+impl<V: Vector> LightSampler<V> {
+    pub fn new(lights: &[Box<dyn Light<V = V>>]) -> Self {
+        let mut unbounded = vec![];
+        let mut entries = vec![];
+        for (index, light) in lights.iter().enumerate() {
+            match light.bounds() {
+                Some(bounds) if bounds.power > 0.0 => entries.push((bounds, index)),
+                Some(_) => {} // gives no light anyway
+                None => unbounded.push(index),
+            }
+        }
+
+        let power = if entries.is_empty() {
+            None
+        } else {
+            Some(Distribution1D::new(entries.iter().map(|(b, _)| b.power)))
+        };
+        let bounded: Vec<usize> = entries.iter().map(|&(_, i)| i).collect();
+        let mut nodes = vec![];
+        if !entries.is_empty() {
+            Self::build(&mut nodes, &mut entries);
+        }
+
+        let mut parents = vec![0; nodes.len()];
+        let mut leaves = vec![0; lights.len()];
+        for (index, node) in nodes.iter().enumerate() {
+            match node {
+                Node::Leaf { light, .. } => leaves[*light] = index,
+                Node::Branch { right, .. } => {
+                    parents[index + 1] = index;
+                    parents[*right] = index;
+                }
+            }
+        }
+        let mut light_entries: Vec<_> = lights.iter().map(|_| Entry::Unlit).collect();
+        for &light in &unbounded {
+            light_entries[light] = Entry::Unbounded;
+        }
+        for (power, &light) in bounded.iter().enumerate() {
+            let leaf = leaves[light];
+            light_entries[light] = Entry::Bounded { power, leaf };
+        }
+
+        Self {
+            unbounded,
+            bounded,
+            power,
+            nodes,
+            parents,
+            entries: light_entries,
+        }
+    }
+
+    fn build(nodes: &mut Vec<Node<V>>, entries: &mut [(LightBounds<V>, usize)]) {
+        if let [(bounds, light)] = entries {
+            nodes.push(Node::Leaf {
+                bounds: *bounds,
+                light: *light,
+            });
+            return;
+        }
+        let bounds = entries
+            .iter()
+            .map(|(b, _)| *b)
+            .reduce(|l, r| l.union(&r))
+            .unwrap();
+
+        // split at the median along the axis where the centers are spread out the most
+        let axis = BoundingBox::around_points(entries.iter().map(|(b, _)| b.bounds.center()))
+            .unwrap()
+            .longest_axis();
+        entries.sort_by(|(l, _), (r, _)| {
+            let l = l.bounds.center().component(axis);
+            let r = r.bounds.center().component(axis);
+            l.partial_cmp(&r).unwrap()
+        });
+        let middle = entries.len() / 2;
+
+        let index = nodes.len();
+        nodes.push(Node::Branch { bounds, right: 0 });
+        let (left, right) = entries.split_at_mut(middle);
+        Self::build(nodes, left);
+        let right_index = nodes.len();
+        Self::build(nodes, right);
+        nodes[index] = Node::Branch {
+            bounds,
+            right: right_index,
+        };
+    }
+
+    /// how likely each choice of `pick_unbounded` is
+    fn unbounded_probability(&self) -> Component {
+        let choices = self.unbounded.len() + usize::from(!self.bounded.is_empty());
+        1.0 / choices as Component
+    }
+
+    /// Picks between the unbounded lights and the bounded ones as a whole, uniformly.
+    ///
+    /// Gives the index of an unbounded light, or `None` for the bounded ones, along with the probability.
+    fn pick_unbounded(&self) -> Option<(Option<usize>, Component)> {
+        let choices = self.unbounded.len() + usize::from(!self.bounded.is_empty());
+        if choices == 0 {
+            return None;
+        }
+        let choice = thread_rng().gen_range(0, choices);
+        Some((
+            self.unbounded.get(choice).copied(),
+            self.unbounded_probability(),
+        ))
+    }
+
+    /// a light's index in the scene, picking bounded ones in proportion to their power, along with the probability of picking it
+    pub fn sample_by_power(&self) -> Option<(usize, Component)> {
+        match self.pick_unbounded()? {
+            (Some(light), probability) => Some((light, probability)),
+            (None, probability) => {
+                let (index, power_probability) = self.power.as_ref()?.sample();
+                Some((self.bounded[index], probability * power_probability))
+            }
+        }
+    }
+
+    /// how likely `sample_by_power` is to pick the light
+    pub fn power_probability(&self, light: LightId) -> Component {
+        match (self.entries.get(light.0), &self.power) {
+            (Some(Entry::Unbounded), _) => self.unbounded_probability(),
+            (Some(Entry::Bounded { power, .. }), Some(distribution)) => {
+                self.unbounded_probability() * distribution.probability(*power)
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// a light's index in the scene, picking bounded ones in proportion to their estimated contribution at the point,
+    /// along with the probability of picking it
+    pub fn sample_by_importance(
+        &self,
+        point: V,
+        normal: Normalized<V>,
+    ) -> Option<(usize, Component)> {
+        let (light, mut probability) = self.pick_unbounded()?;
+        if let Some(light) = light {
+            return Some((light, probability));
+        }
+
+        let mut rng = thread_rng();
+        let mut index = 0;
+        loop {
+            match &self.nodes[index] {
+                Node::Leaf { light, .. } => return Some((*light, probability)),
+                Node::Branch { right, .. } => {
+                    let left = index + 1;
+                    let left_importance = self.nodes[left].bounds().importance(point, normal);
+                    let right_importance = self.nodes[*right].bounds().importance(point, normal);
+                    let total = left_importance + right_importance;
+                    if total <= 0.0 {
+                        return None; // nothing here can reach the point
+                    }
+                    let left_probability = left_importance / total;
+                    if rng.gen::<Component>() < left_probability {
+                        probability *= left_probability;
+                        index = left;
+                    } else {
+                        probability *= 1.0 - left_probability;
+                        index = *right;
+                    }
+                }
+            }
+        }
+    }
+
+    /// how likely `sample_by_importance` is to pick the light at the point
+    pub fn importance_probability(
+        &self,
+        light: LightId,
+        point: V,
+        normal: Normalized<V>,
+    ) -> Component {
+        let leaf = match self.entries.get(light.0) {
+            Some(Entry::Unbounded) => return self.unbounded_probability(),
+            Some(Entry::Bounded { leaf, .. }) => *leaf,
+            Some(Entry::Unlit) | None => return 0.0,
+        };
+
+        // the choices made on the way down, retraced from the leaf up
+        let mut probability = self.unbounded_probability();
+        let mut index = leaf;
+        while index != 0 {
+            let parent = self.parents[index];
+            let right = match &self.nodes[parent] {
+                Node::Branch { right, .. } => *right,
+                Node::Leaf { .. } => unreachable!("leaves have no children"),
+            };
+            let left_importance = self.nodes[parent + 1].bounds().importance(point, normal);
+            let right_importance = self.nodes[right].bounds().importance(point, normal);
+            let total = left_importance + right_importance;
+            if total <= 0.0 {
+                return 0.0;
+            }
+            let left_probability = left_importance / total;
+            probability *= if index == right {
+                1.0 - left_probability
+            } else {
+                left_probability
+            };
+            index = parent;
+        }
+        probability
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// point lights of different strengths spread along x, one of them unlit, a spot light and a sun
+    fn lights() -> Vec<Box<dyn Light<V = Vec3>>> {
+        let mut lights: Vec<Box<dyn Light<V = Vec3>>> = (0..9)
+            .map(|i| {
+                Box::new(PointLight {
+                    position: Vec3::new(
+                        i as Component * 1.5 - 6.0,
+                        (i % 3) as Component,
+                        -((i % 4) as Component),
+                    ),
+                    color: Color::white(),
+                    intensity: if i == 4 { 0.0 } else { 1.0 + i as Component },
+                    photometry: None,
+                }) as Box<dyn Light<V = Vec3>>
+            })
+            .collect();
+        lights.push(Box::new(SpotLight {
+            position: Vec3::new(0.0, 5.0, 0.0),
+            direction: Vec3::new(0.0, -1.0, 0.0).normalized(),
+            color: Color::white(),
+            intensity: 20.0,
+            angle: 0.5,
+            softness: 0.1,
+            photometry: None,
+        }));
+        lights.push(Box::new(DirectionalLight {
+            direction: Vec3::new(0.0, -1.0, 0.2).normalized(),
+            color: Color::white(),
+            irradiance: 3.0,
+            angular_diameter: 0.0,
+        }));
+        lights
+    }
+
+    /// points to light, with the normals of their surfaces
+    fn points() -> Vec<(Vec3, Normalized<Vec3>)> {
+        vec![
+            (
+                Vec3::new(0.0, -1.0, 0.0),
+                Vec3::new(0.0, 1.0, 0.0).normalized(),
+            ),
+            (
+                Vec3::new(-5.0, 0.5, -2.0),
+                Vec3::new(0.3, 0.2, 1.0).normalized(),
+            ),
+            (
+                Vec3::new(20.0, 3.0, 4.0),
+                Vec3::new(-1.0, 0.0, 0.0).normalized(),
+            ),
+        ]
+    }
+
+    #[test]
+    fn power_probabilities_sum_to_one() {
+        let lights = lights();
+        let sampler = LightSampler::new(&lights);
+        let total: Component = (0..lights.len())
+            .map(|i| sampler.power_probability(LightId(i)))
+            .sum();
+        assert!((total - 1.0).abs() < 1e-5, "{}", total);
+        assert_eq!(sampler.power_probability(LightId(4)), 0.0);
+    }
+
+    #[test]
+    fn importance_probabilities_sum_to_one() {
+        let lights = lights();
+        let sampler = LightSampler::new(&lights);
+        for (point, normal) in points() {
+            let total: Component = (0..lights.len())
+                .map(|i| sampler.importance_probability(LightId(i), point, normal))
+                .sum();
+            assert!((total - 1.0).abs() < 1e-5, "{} at {}", total, point);
+            assert_eq!(
+                sampler.importance_probability(LightId(4), point, normal),
+                0.0
+            );
+        }
+    }
+
+    #[test]
+    fn sampling_matches_the_probabilities() {
+        const SAMPLES: usize = 100_000;
+        let lights = lights();
+        let sampler = LightSampler::new(&lights);
+        for (point, normal) in points() {
+            let mut counts = vec![0; lights.len()];
+            for _ in 0..SAMPLES {
+                let (index, probability) = sampler.sample_by_importance(point, normal).unwrap();
+                let expected = sampler.importance_probability(LightId(index), point, normal);
+                assert!((probability - expected).abs() < 1e-5);
+                counts[index] += 1;
+            }
+            for (index, &count) in counts.iter().enumerate() {
+                let frequency = count as Component / SAMPLES as Component;
+                let probability = sampler.importance_probability(LightId(index), point, normal);
+                assert!(
+                    (frequency - probability).abs() < 0.01,
+                    "light {} at {}",
+                    index,
+                    point
+                );
+            }
+        }
+
+        let mut counts = vec![0; lights.len()];
+        for _ in 0..SAMPLES {
+            let (index, probability) = sampler.sample_by_power().unwrap();
+            assert!((probability - sampler.power_probability(LightId(index))).abs() < 1e-5);
+            counts[index] += 1;
+        }
+        for (index, &count) in counts.iter().enumerate() {
+            let frequency = count as Component / SAMPLES as Component;
+            assert!((frequency - sampler.power_probability(LightId(index))).abs() < 0.01);
+        }
+    }
+}
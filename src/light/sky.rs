@@ -0,0 +1,384 @@
+use super::*;
+use std::f32::consts;
+
+/// solar illuminance above the atmosphere, in lux
+const EXTRATERRESTRIAL_ILLUMINANCE: Component = 127_500.0;
+
+/// resolution of the image the sky is baked into to guide sampling
+const GUIDE_WIDTH: usize = 128;
+const GUIDE_HEIGHT: usize = 64;
+
+/// The direction towards the sun, with north towards positive z and east towards positive x.
+///
+/// `latitude` is in degrees (negative for the southern hemisphere),
+/// `day_of_year` counts from 1 on January 1st, and `solar_hour` is local solar time, with the sun highest at 12.
+pub fn sun_direction(
+    latitude: Component,
+    day_of_year: u32,
+    solar_hour: Component,
+) -> Normalized<Vec3> {
+    let latitude = latitude.to_radians();
+    let declination = -(23.44 as Component).to_radians()
+        * (consts::TAU / 365.0 * (day_of_year as Component + 10.0)).cos();
+    let hour_angle = (15.0 * (solar_hour - 12.0)).to_radians();
+
+    let sin_elevation =
+        latitude.sin() * declination.sin() + latitude.cos() * declination.cos() * hour_angle.cos();
+    let elevation = sin_elevation.clamp(-1.0, 1.0).asin();
+
+    // measured clockwise from north
+    let cos_azimuth = (declination.sin() - sin_elevation * latitude.sin())
+        / (elevation.cos() * latitude.cos()).max(1e-6);
+    let mut azimuth = cos_azimuth.clamp(-1.0, 1.0).acos();
+    if hour_angle > 0.0 {
+        azimuth = consts::TAU - azimuth; // afternoon, so west of the meridian
+    }
+
+    Vec3::new(
+        azimuth.sin() * elevation.cos(),
+        elevation.sin(),
+        azimuth.cos() * elevation.cos(),
+    )
+    .normalized()
+}
+
+/// The five coefficients of the Perez sky luminance distribution.
+#[derive(Debug, Copy, Clone)]
+struct Perez([Component; 5]);
+
+impl Perez {
+    fn new(turbidity: Component, coefficients: [(Component, Component); 5]) -> Self {
+        let mut values = [0.0; 5];
+        for (value, (slope, offset)) in values.iter_mut().zip(coefficients.iter()) {
+            *value = slope * turbidity + offset;
+        }
+        Perez(values)
+    }
+
+    /// relative to the zenith, with `theta` the angle from the zenith and `gamma` the angle from the sun
+    fn f(&self, cos_theta: Component, gamma: Component) -> Component {
+        let [a, b, c, d, e] = self.0;
+        (1.0 + a * (b / cos_theta).exp()) * (1.0 + c * (d * gamma).exp() + e * gamma.cos().powi(2))
+    }
+}
+
+/// Daylight from a clear or hazy sky following Preetham, Shirley and Smits' analytic model, for outdoor scenes.
+///
+/// Below the horizon it shows a diffuse ground lit by the sky and sun.
+/// The sun itself is a separate light, since it's far brighter than the rest of the sky; see `sun`.
+/// Values are in the same units as `EmissionStrength::Lumens`, so daylight calls for a matching exposure or `intensity`.
+pub struct PreethamSky {
+    sun_direction: Normalized<Vec3>,
+    turbidity: Component,
+    ground_albedo: Color,
+    /// scales everything the sky emits, including its sun
+    pub intensity: Component,
+    perez: [Perez; 3],
+    /// luminance and chromaticity at the zenith
+    zenith: [Component; 3],
+    ground: Color,
+    /// the sky baked into an image, whose importance sampling is close enough to the real thing
+    guide: EnvironmentMap,
+}
+
+impl PreethamSky {
+    /// `turbidity` ranges from about 2 for a very clear sky to 10 for a hazy one
+    pub fn new(
+        sun_direction: Normalized<Vec3>,
+        turbidity: Component,
+        ground_albedo: Color,
+    ) -> Self {
+        let t = turbidity.clamp(1.7, 10.0);
+        let perez = [
+            Perez::new(
+                t,
+                [
+                    (0.1787, -1.4630),
+                    (-0.3554, 0.4275),
+                    (-0.0227, 5.3251),
+                    (0.1206, -2.5771),
+                    (-0.0670, 0.3703),
+                ],
+            ),
+            Perez::new(
+                t,
+                [
+                    (-0.0193, -0.2592),
+                    (-0.0665, 0.0008),
+                    (-0.0004, 0.2125),
+                    (-0.0641, -0.8989),
+                    (-0.0033, 0.0452),
+                ],
+            ),
+            Perez::new(
+                t,
+                [
+                    (-0.0167, -0.2608),
+                    (-0.0950, 0.0092),
+                    (-0.0079, 0.2102),
+                    (-0.0441, -1.6537),
+                    (-0.0109, 0.0529),
+                ],
+            ),
+        ];
+
+        // the model breaks down once the sun sets, so keep it just above the horizon
+        let theta = sun_direction
+            .y
+            .clamp(0.0, 1.0)
+            .acos()
+            .min(consts::FRAC_PI_2 - 0.01);
+        let chi = (4.0 / 9.0 - t / 120.0) * (consts::PI - 2.0 * theta);
+        let luminance = ((4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192).max(0.0);
+        let polynomial = |c: [[Component; 4]; 3]| {
+            let row = |r: [Component; 4]| {
+                r[0] * theta.powi(3) + r[1] * theta.powi(2) + r[2] * theta + r[3]
+            };
+            t * t * row(c[0]) + t * row(c[1]) + row(c[2])
+        };
+        let x = polynomial([
+            [0.00166, -0.00375, 0.00209, 0.0],
+            [-0.02903, 0.06377, -0.03202, 0.00394],
+            [0.11693, -0.21196, 0.06052, 0.25886],
+        ]);
+        let y = polynomial([
+            [0.00275, -0.00610, 0.00317, 0.0],
+            [-0.04214, 0.08970, -0.04153, 0.00516],
+            [0.15346, -0.26756, 0.06670, 0.26688],
+        ]);
+
+        let mut sky = Self {
+            sun_direction,
+            turbidity: t,
+            ground_albedo,
+            intensity: 1.0,
+            perez,
+            zenith: [luminance, x, y],
+            ground: Color::black(),
+            guide: EnvironmentMap::new(Image::new(1, 1)),
+        };
+
+        let mut image = Image::new(GUIDE_WIDTH, GUIDE_HEIGHT);
+        let mut sky_irradiance = Color::black();
+        for row in 0..GUIDE_HEIGHT / 2 {
+            for column in 0..GUIDE_WIDTH {
+                let direction = sky.guide_direction(column, row);
+                let color = sky.sky_radiance(direction);
+                let index = image.index(column, row);
+                image.pixels_mut()[index] = color;
+                // sum up the light falling onto the ground, weighing each pixel by its solid angle
+                let polar_top = row as Component / GUIDE_HEIGHT as Component * consts::PI;
+                let polar_bottom = (row + 1) as Component / GUIDE_HEIGHT as Component * consts::PI;
+                let solid_angle =
+                    consts::TAU / GUIDE_WIDTH as Component * (polar_top.cos() - polar_bottom.cos());
+                sky_irradiance += color * (solid_angle * direction.y);
+            }
+        }
+        let sun = sky.sun();
+        let sun_irradiance = sun.color * (sun.irradiance * sun_direction.y.max(0.0));
+        sky.ground = (sky_irradiance + sun_irradiance) * ground_albedo / consts::PI;
+        sky.ground.alpha = 1.0;
+        for row in GUIDE_HEIGHT / 2..GUIDE_HEIGHT {
+            for column in 0..GUIDE_WIDTH {
+                let index = image.index(column, row);
+                image.pixels_mut()[index] = sky.ground;
+            }
+        }
+        sky.guide = EnvironmentMap::new(image);
+        sky
+    }
+
+    pub fn sun_direction(&self) -> Normalized<Vec3> {
+        self.sun_direction
+    }
+
+    pub fn turbidity(&self) -> Component {
+        self.turbidity
+    }
+
+    pub fn ground_albedo(&self) -> Color {
+        self.ground_albedo
+    }
+
+    fn guide_direction(&self, column: usize, row: usize) -> Normalized<Vec3> {
+        let azimuth = ((column as Component + 0.5) / GUIDE_WIDTH as Component - 0.5) * consts::TAU;
+        let polar = (row as Component + 0.5) / GUIDE_HEIGHT as Component * consts::PI;
+        Vec3::new(
+            polar.sin() * azimuth.sin(),
+            polar.cos(),
+            polar.sin() * azimuth.cos(),
+        )
+        .normalized()
+    }
+
+    /// the sky above the horizon, without the sun's disk and before applying `intensity`
+    fn sky_radiance(&self, direction: Normalized<Vec3>) -> Color {
+        // the model isn't meant for the horizon itself
+        let cos_theta = direction.y.max(0.01);
+        let gamma = self.sun_direction.dot(direction).clamp(-1.0, 1.0).acos();
+        let sun_theta = self
+            .sun_direction
+            .y
+            .clamp(0.0, 1.0)
+            .acos()
+            .min(consts::FRAC_PI_2 - 0.01);
+        let [luminance, x, y] = {
+            let mut values = [0.0; 3];
+            for (value, (perez, zenith)) in values
+                .iter_mut()
+                .zip(self.perez.iter().zip(self.zenith.iter()))
+            {
+                *value = zenith * perez.f(cos_theta, gamma) / perez.f(1.0, sun_theta);
+            }
+            values
+        };
+        // from kilocandela per square meter to the units of `EmissionStrength::Lumens`
+        xyy_to_rgb(luminance * 1000.0 / LUMENS_PER_WATT, x, y)
+    }
+
+    /// the light arriving from the given direction, excluding the sun
+    pub fn radiance(&self, direction: Normalized<Vec3>) -> Color {
+        let color = if direction.y >= 0.0 {
+            self.sky_radiance(direction)
+        } else {
+            self.ground
+        };
+        color * self.intensity
+    }
+
+    /// The sun, attenuated by the atmosphere along its path according to the turbidity.
+    ///
+    /// Below the horizon, it gives no light.
+    pub fn sun(&self) -> DirectionalLight {
+        let cos_zenith = self.sun_direction.y;
+        let color = if cos_zenith <= 0.0 {
+            Color::black()
+        } else {
+            // how much more air the light passes through than when coming from straight above
+            let zenith_degrees = cos_zenith.acos().to_degrees();
+            let air_mass = 1.0 / (cos_zenith + 0.15 * (93.885 - zenith_degrees).powf(-1.253));
+            let beta = 0.04608 * self.turbidity - 0.04586;
+            // Rayleigh scattering and aerosols at rough wavelengths for each channel, in micrometers
+            let transmittance = |wavelength: Component| {
+                let rayleigh = 0.008735 * wavelength.powf(-4.08);
+                let aerosol = beta * wavelength.powf(-1.3);
+                (-(rayleigh + aerosol) * air_mass).exp()
+            };
+            Color::new(
+                transmittance(0.65),
+                transmittance(0.55),
+                transmittance(0.45),
+                1.0,
+            )
+        };
+        DirectionalLight {
+            direction: -self.sun_direction,
+            color,
+            irradiance: EXTRATERRESTRIAL_ILLUMINANCE / LUMENS_PER_WATT * self.intensity,
+            angular_diameter: DirectionalLight::SUN_ANGULAR_DIAMETER,
+        }
+    }
+}
+
+/// converts luminance and CIE xy chromaticity to linear sRGB
+fn xyy_to_rgb(luminance: Component, x: Component, y: Component) -> Color {
+    if y <= 0.0 {
+        return Color::black();
+    }
+    let big_x = x * luminance / y;
+    let big_z = (1.0 - x - y) * luminance / y;
+    Color::new(
+        (3.2406 * big_x - 1.5372 * luminance - 0.4986 * big_z).max(0.0),
+        (-0.9689 * big_x + 1.8758 * luminance + 0.0415 * big_z).max(0.0),
+        (0.0557 * big_x - 0.2040 * luminance + 1.0570 * big_z).max(0.0),
+        1.0,
+    )
+}
+
+impl Light for PreethamSky {
+    type V = Vec3;
+
+    fn sample(&self, point: Vec3) -> Option<LightSample<Vec3>> {
+        let guide = self.guide.sample(point)?;
+        let pdf = self.guide.pdf(guide.direction);
+        if pdf <= 0.0 {
+            return None;
+        }
+        Some(LightSample {
+            radiance: self.radiance(guide.direction) / pdf,
+            pdf,
+            ..guide
+        })
+    }
+
+    fn escaped_radiance(&self, direction: Normalized<Vec3>) -> Color {
+        self.radiance(direction)
+    }
+
+    fn pdf(&self, _point: Vec3, direction: Normalized<Vec3>) -> Component {
+        self.guide.pdf(direction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the day of the March equinox, when the sun is above the equator
+    const EQUINOX: u32 = 81;
+    const SOLSTICE: u32 = 172;
+
+    fn assert_direction(actual: Normalized<Vec3>, expected: Vec3) {
+        let expected = expected.normalized();
+        assert!(
+            actual.dot(expected) > 0.9995,
+            "{} != {}",
+            *actual,
+            *expected
+        );
+    }
+
+    fn elevation(direction: Normalized<Vec3>) -> Component {
+        direction.y.asin().to_degrees()
+    }
+
+    #[test]
+    fn sun_is_overhead_at_the_equator_at_noon_on_the_equinox() {
+        assert_direction(sun_direction(0.0, EQUINOX, 12.0), Vec3::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn sun_is_south_at_noon_in_the_north_and_north_in_the_south() {
+        assert_direction(
+            sun_direction(45.0, EQUINOX, 12.0),
+            Vec3::new(0.0, 1.0, -1.0),
+        );
+        assert_direction(
+            sun_direction(-45.0, EQUINOX, 12.0),
+            Vec3::new(0.0, 1.0, 1.0),
+        );
+    }
+
+    #[test]
+    fn sun_rises_in_the_east_and_sets_in_the_west_on_the_equinox() {
+        assert_direction(sun_direction(45.0, EQUINOX, 6.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_direction(
+            sun_direction(45.0, EQUINOX, 18.0),
+            Vec3::new(-1.0, 0.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn sun_is_highest_on_the_summer_solstice() {
+        let noon = elevation(sun_direction(45.0, SOLSTICE, 12.0));
+        assert!((noon - (90.0 - 45.0 + 23.44)).abs() < 0.1, "{}", noon);
+        assert!(elevation(sun_direction(45.0, SOLSTICE, 9.0)) < noon);
+    }
+
+    #[test]
+    fn afternoon_mirrors_the_morning() {
+        let morning = sun_direction(30.0, 100, 9.5);
+        let afternoon = sun_direction(30.0, 100, 14.5);
+        assert_direction(afternoon, Vec3::new(-morning.x, morning.y, morning.z));
+    }
+}
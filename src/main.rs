@@ -1,27 +1,46 @@
+extern crate exr;
 extern crate image as image_lib;
 extern crate num_cpus;
 extern crate rand;
 extern crate rayon;
 
+mod bvh;
 mod camera;
 mod color;
 mod image;
+mod integrator;
+mod light;
 mod material;
+mod mesh;
 mod rendering;
+mod sampling;
 mod scene;
+mod scene_graph;
 mod shape;
+mod texture;
 mod tracing;
+mod transformed;
 mod vectors;
+mod visibility;
 
 pub use crate::image::*;
+pub use bvh::*;
 pub use camera::*;
 pub use color::*;
+pub use integrator::*;
+pub use light::*;
 pub use material::*;
+pub use mesh::*;
 pub use rendering::*;
+pub use sampling::*;
 pub use scene::*;
+pub use scene_graph::*;
 pub use shape::*;
+pub use texture::*;
 pub use tracing::*;
+pub use transformed::*;
 pub use vectors::*;
+pub use visibility::*;
 
 use std::fs;
 use std::time::*;
@@ -38,8 +57,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let diffuse = DiffuseMaterial {
         color: Color::white(),
     };
-    let light = FlatColorMaterial {
-        color: Color::new_gray(5.0, 1.0),
+    let light = EmissiveMaterial {
+        color: Color::white(),
+        strength: EmissionStrength::Radiance(5.0),
+        is_two_sided: false,
     };
     let mirror = MirrorMaterial;
 
@@ -76,7 +97,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             (Color::white(), Vec3::new(0.0, -1000.0, 5.0)),
             (Color::white(), Vec3::new(0.0, 1000.0, 5.0)),
             (Color::white(), Vec3::new(0.0, 0.0, 1000.0)),
-            (Color::white(), Vec3::new(0.0, 0.0, -1000.0)),
+            (Color::white(), Vec3::new(0.0, 0.0, -1015.0)),
         ];
         for (color, center) in details {
             scene.add(MaterialShape {
@@ -91,13 +112,19 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let raytracer = Raytracer {
         camera,
-        element: scene,
+        scene,
+        integrator: Box::new(MisPathTracer),
     };
 
     let options = TracingOptions {
         background_color: Color::clear(),
-        max_bounces: 5,
+        max_bounces: 16,
+        max_diffuse_bounces: 4,
+        max_glossy_bounces: 8,
+        max_transmission_bounces: 12,
+        russian_roulette_depth: 3,
         near_clipping: 0.0001,
+        light_selection: LightSelection::Tree,
     };
 
     let start = Instant::now();
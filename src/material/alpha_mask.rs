@@ -0,0 +1,44 @@
+use super::*;
+
+/// How an opacity texture decides whether rays hit the surface.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AlphaMode {
+    /// rays pass through wherever the opacity is below the threshold, for hard cutouts like leaves or fences
+    Threshold(Component),
+    /// rays pass through with a probability of one minus the opacity, for partially transparent surfaces
+    Stochastic,
+}
+
+/// Cuts holes into another material according to an opacity texture.
+pub struct AlphaMaskedMaterial<M, T> {
+    pub material: M,
+    pub opacity: T,
+    pub mode: AlphaMode,
+}
+
+impl<V: Vector, M: Material<V>, T: Texture<V>> Material<V> for AlphaMaskedMaterial<M, T> {
+    fn behavior(&self, hit: Hit<V>) -> Behavior<V> {
+        self.material.behavior(hit)
+    }
+
+    fn evaluate(&self, hit: &Hit<V>, direction: Normalized<V>) -> Color {
+        self.material.evaluate(hit, direction)
+    }
+
+    fn pdf(&self, hit: &Hit<V>, direction: Normalized<V>) -> Component {
+        self.material.pdf(hit, direction)
+    }
+
+    fn has_cutouts(&self) -> bool {
+        true
+    }
+
+    fn opacity(&self, hit: &Hit<V>) -> Component {
+        let opacity = self.opacity.value_at(hit) * self.material.opacity(hit);
+        match self.mode {
+            AlphaMode::Threshold(threshold) if opacity < threshold => 0.0,
+            AlphaMode::Threshold(_) => 1.0,
+            AlphaMode::Stochastic => opacity.clamp(0.0, 1.0),
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use super::*;
+use std::f32::consts;
+
+pub struct FlatColorMaterial<T = Color> {
+    pub color: T,
+}
+
+impl<V: Vector, T: Texture<V>> Material<V> for FlatColorMaterial<T> {
+    fn behavior(&self, hit: Hit<V>) -> Behavior<V> {
+        let color = self.color.color_at(&hit);
+        Behavior {
+            emission: color,
+            color,
+            next_bounce: None,
+            lobe: Lobe::Diffuse,
+            is_specular: false,
+        }
+    }
+}
+
+pub struct MirrorMaterial;
+
+impl<V: Vector> Material<V> for MirrorMaterial {
+    fn behavior(&self, hit: Hit<V>) -> Behavior<V> {
+        let normal = hit.normal;
+        let dir = hit.ray_direction;
+        let reflected = dir - normal * 2.0 * (dir.dot(normal));
+        Behavior {
+            emission: Color::black(),
+            color: Color::white(),
+            next_bounce: Some(hit.next_ray(reflected)),
+            lobe: Lobe::Glossy,
+            is_specular: true,
+        }
+    }
+}
+
+pub struct DiffuseMaterial<T = Color> {
+    pub color: T,
+}
+
+impl<T: Texture<Vec3>> Material<Vec3> for DiffuseMaterial<T> {
+    fn behavior(&self, hit: Hit<Vec3>) -> Behavior<Vec3> {
+        let frame = Frame::facing(&hit);
+        let incoming = cosine_weighted_hemisphere();
+        // the cosine and 1/π cancel out with the pdf
+        Behavior {
+            emission: Color::black(),
+            color: self.color.color_at(&hit),
+            next_bounce: Some(hit.next_ray(frame.to_world(incoming))),
+            lobe: Lobe::Diffuse,
+            is_specular: false,
+        }
+    }
+
+    fn evaluate(&self, hit: &Hit<Vec3>, direction: Normalized<Vec3>) -> Color {
+        let cos = hit.facing_normal().dot(direction);
+        if cos <= 0.0 {
+            return Color::black();
+        }
+        self.color.color_at(hit) * (cos / consts::PI)
+    }
+
+    fn pdf(&self, hit: &Hit<Vec3>, direction: Normalized<Vec3>) -> Component {
+        hit.facing_normal().dot(direction).max(0.0) / consts::PI
+    }
+}
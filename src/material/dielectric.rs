@@ -0,0 +1,187 @@
+use super::*;
+use rand::*;
+
+/// Glass-like material that reflects or refracts off a rough surface of GGX-distributed microfacets, e.g. for frosted glass.
+///
+/// Hits on the back of a surface are treated as leaving the material.
+pub struct RoughDielectricMaterial<T = Color, R = Component> {
+    /// tints light passing through the surface
+    pub tint: T,
+    /// 0 for clear glass, 1 for very rough
+    pub roughness: R,
+    pub ior: Component,
+}
+
+impl<T: Texture<Vec3>, R: Texture<Vec3>> Material<Vec3> for RoughDielectricMaterial<T, R> {
+    fn behavior(&self, hit: Hit<Vec3>) -> Behavior<Vec3> {
+        let frame = Frame::facing(&hit);
+        let distribution = GgxDistribution::new(self.roughness.value_at(&hit), 0.0);
+        let outgoing = frame.to_local(-hit.ray_direction);
+        let microfacet_normal = distribution.sample_visible_normal(outgoing);
+
+        let eta = if hit.is_back_face() {
+            self.ior
+        } else {
+            1.0 / self.ior
+        };
+        let fresnel = dielectric_fresnel(outgoing.dot(microfacet_normal), eta);
+
+        let (incoming, tint) = if thread_rng().gen::<Component>() < fresnel {
+            (reflect(outgoing, microfacet_normal), Color::white())
+        } else {
+            match refract(outgoing, microfacet_normal, eta) {
+                Some(refracted) => (refracted, self.tint.color_at(&hit)),
+                // the fresnel term is 1 for total internal reflection, so this is just for numerical edge cases
+                None => (reflect(outgoing, microfacet_normal), Color::white()),
+            }
+        };
+
+        // reflections must stay above the surface and refractions below it
+        let is_reflection = outgoing.dot(microfacet_normal) * incoming.dot(microfacet_normal) > 0.0;
+        let lobe = if is_reflection {
+            Lobe::Glossy
+        } else {
+            Lobe::Transmission
+        };
+        if is_reflection != (incoming.z > 0.0) {
+            return Behavior {
+                emission: Color::black(),
+                color: Color::black(),
+                next_bounce: None,
+                lobe,
+                is_specular: false,
+            };
+        }
+
+        let shadowing = distribution.g2(outgoing, incoming) / distribution.g1(outgoing);
+        Behavior {
+            emission: Color::black(),
+            color: tint * shadowing,
+            next_bounce: Some(hit.next_ray(frame.to_world(incoming))),
+            lobe,
+            is_specular: false,
+        }
+    }
+
+    fn evaluate(&self, hit: &Hit<Vec3>, direction: Normalized<Vec3>) -> Color {
+        let frame = Frame::facing(hit);
+        let distribution = GgxDistribution::new(self.roughness.value_at(hit), 0.0);
+        let outgoing = frame.to_local(-hit.ray_direction);
+        let incoming = frame.to_local(direction);
+        let eta = if hit.is_back_face() {
+            self.ior
+        } else {
+            1.0 / self.ior
+        };
+
+        if incoming.z > 0.0 {
+            return match distribution.reflection(outgoing, incoming) {
+                Some((value, half_vector)) => {
+                    Color::white() * (dielectric_fresnel(outgoing.dot(half_vector), eta) * value)
+                }
+                None => Color::black(),
+            };
+        }
+        if outgoing.z <= 0.0 || incoming.z == 0.0 {
+            return Color::black();
+        }
+
+        // the generalized half vector for refraction, following Walter et al. 2007
+        let mut half_vector: Vec3 = (outgoing + incoming / eta).normalized().into();
+        if half_vector.z < 0.0 {
+            half_vector = -half_vector;
+        }
+        let cos_outgoing = outgoing.dot(half_vector);
+        let cos_incoming = incoming.dot(half_vector);
+        if cos_outgoing * cos_incoming >= 0.0 {
+            return Color::black();
+        }
+        let denominator = cos_outgoing + cos_incoming / eta;
+        let fresnel = dielectric_fresnel(cos_outgoing, eta);
+        // leaves out the compression of radiance on entering a denser medium, like sampling does
+        let value = (1.0 - fresnel)
+            * distribution.d(half_vector)
+            * distribution.g2(outgoing, incoming)
+            * (cos_outgoing * cos_incoming).abs()
+            / (outgoing.z * eta * eta * denominator * denominator);
+        self.tint.color_at(hit) * value
+    }
+
+    fn pdf(&self, hit: &Hit<Vec3>, direction: Normalized<Vec3>) -> Component {
+        let frame = Frame::facing(hit);
+        let distribution = GgxDistribution::new(self.roughness.value_at(hit), 0.0);
+        let outgoing = frame.to_local(-hit.ray_direction);
+        let incoming = frame.to_local(direction);
+        let eta = if hit.is_back_face() {
+            self.ior
+        } else {
+            1.0 / self.ior
+        };
+
+        if incoming.z > 0.0 {
+            let pdf = distribution.reflection_pdf(outgoing, incoming);
+            if pdf <= 0.0 {
+                return 0.0;
+            }
+            let half_vector = (outgoing + incoming).normalized();
+            return dielectric_fresnel(outgoing.dot(half_vector), eta) * pdf;
+        }
+        if outgoing.z <= 0.0 || incoming.z == 0.0 {
+            return 0.0;
+        }
+
+        let mut half_vector: Vec3 = (outgoing + incoming / eta).normalized().into();
+        if half_vector.z < 0.0 {
+            half_vector = -half_vector;
+        }
+        let cos_outgoing = outgoing.dot(half_vector);
+        let cos_incoming = incoming.dot(half_vector);
+        if cos_outgoing <= 0.0 || cos_incoming >= 0.0 {
+            return 0.0;
+        }
+        // the density of visible normals, times how much refraction squeezes directions around them
+        let visible =
+            distribution.g1(outgoing) * cos_outgoing * distribution.d(half_vector) / outgoing.z;
+        let denominator = eta * cos_outgoing + cos_incoming;
+        let jacobian = -cos_incoming / (denominator * denominator);
+        (1.0 - dielectric_fresnel(cos_outgoing, eta)) * visible * jacobian
+    }
+}
+
+/// An infinitely thin sheet of glass, like a window pane or soap bubble.
+///
+/// Light passes straight through without being offset by refraction,
+/// and reflections account for bouncing back and forth inside the sheet.
+pub struct ThinDielectricMaterial<T = Color> {
+    /// tints light passing through the sheet
+    pub tint: T,
+    pub ior: Component,
+}
+
+impl<T: Texture<Vec3>> Material<Vec3> for ThinDielectricMaterial<T> {
+    fn behavior(&self, hit: Hit<Vec3>) -> Behavior<Vec3> {
+        let normal = hit.facing_normal();
+        let outgoing = -hit.ray_direction;
+        let fresnel = dielectric_fresnel(outgoing.dot(normal), 1.0 / self.ior);
+        // sum of the geometric series of internal reflections
+        let reflectance = 2.0 * fresnel / (1.0 + fresnel);
+
+        if thread_rng().gen::<Component>() < reflectance {
+            Behavior {
+                emission: Color::black(),
+                color: Color::white(),
+                next_bounce: Some(hit.next_ray(reflect(*outgoing, *normal))),
+                lobe: Lobe::Glossy,
+                is_specular: true,
+            }
+        } else {
+            Behavior {
+                emission: Color::black(),
+                color: self.tint.color_at(&hit),
+                next_bounce: Some(hit.next_ray(*hit.ray_direction)),
+                lobe: Lobe::Transmission,
+                is_specular: true,
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use super::*;
+use std::f32::consts;
+
+/// luminous efficacy of monochromatic light at 555 nm, where the eye is most sensitive
+pub(crate) const LUMENS_PER_WATT: Component = 683.0;
+
+/// How brightly a surface emits light.
+///
+/// The total flux of `Watts` and `Lumens` is spread evenly over the surface of an `AreaLight`,
+/// which knows its area; other emissive surfaces emit that much per unit area.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EmissionStrength {
+    /// the radiance leaving the surface, which is what the renderer works with directly
+    Radiance(Component),
+    /// radiant flux in watts
+    Watts(Component),
+    /// luminous flux in lumens
+    Lumens(Component),
+}
+
+impl EmissionStrength {
+    /// the radiance of a diffuse emitter with this strength and surface area, emitting from the given number of sides (1 or 2)
+    pub fn radiance(&self, area: Component, sides: Component) -> Component {
+        match *self {
+            EmissionStrength::Radiance(radiance) => radiance,
+            // a diffuse emitter with radiance L emits π L per unit area on each side
+            EmissionStrength::Watts(power) => power / (consts::PI * area * sides),
+            EmissionStrength::Lumens(flux) => flux / LUMENS_PER_WATT / (consts::PI * area * sides),
+        }
+    }
+}
+
+/// A diffuse light source that emits without reflecting anything.
+pub struct EmissiveMaterial<T = Color> {
+    /// multiplied by the strength, so it's best kept in `0.0..=1.0`
+    pub color: T,
+    pub strength: EmissionStrength,
+    /// one-sided emitters only emit on the side their normal points to
+    pub is_two_sided: bool,
+}
+
+impl<T> EmissiveMaterial<T> {
+    /// the radiance it emits without knowing the area it covers, so `Watts` and `Lumens` are taken per unit area
+    pub fn radiance(&self) -> Component {
+        self.radiance_over(1.0)
+    }
+
+    /// the radiance it emits when covering the given area
+    pub fn radiance_over(&self, area: Component) -> Component {
+        let sides = if self.is_two_sided { 2.0 } else { 1.0 };
+        self.strength.radiance(area, sides)
+    }
+}
+
+impl<V: Vector, T: Texture<V>> Material<V> for EmissiveMaterial<T> {
+    fn behavior(&self, hit: Hit<V>) -> Behavior<V> {
+        let emission = if self.is_two_sided || !hit.is_back_face() {
+            self.color.color_at(&hit) * self.radiance()
+        } else {
+            Color::black()
+        };
+        Behavior {
+            emission,
+            color: Color::black(),
+            next_bounce: None,
+            lobe: Lobe::Diffuse,
+            is_specular: false,
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use super::*;
+
+/// An orthonormal basis around a normal, for working with directions relative to a surface.
+#[derive(Debug, Copy, Clone)]
+pub struct Frame {
+    pub tangent: Normalized<Vec3>,
+    pub bitangent: Normalized<Vec3>,
+    pub normal: Normalized<Vec3>,
+}
+
+impl Frame {
+    /// Builds a basis around the normal, with the tangent as close to the given one as possible.
+    pub fn new(normal: Normalized<Vec3>, tangent: Vec3) -> Self {
+        let mut projected = tangent - normal * normal.dot(tangent);
+        if projected.squared_sum() < 1e-12 {
+            // the tangent is useless, so pick any perpendicular direction
+            let axis = if normal.x.abs() < 0.9 {
+                Vec3::positive_x()
+            } else {
+                Vec3::positive_y()
+            };
+            projected = (*normal).cross(axis);
+        }
+        let tangent = projected.normalized();
+        Self {
+            tangent,
+            bitangent: tangent.cross(normal),
+            normal,
+        }
+    }
+
+    /// Around the hit's shading normal, aligned with its tangent and with the bitangent on the same side as the hit's,
+    /// so the frame follows the texture coordinates even where they're mirrored.
+    pub fn from_hit(hit: &Hit<Vec3>) -> Self {
+        let frame = Self::new(hit.normal, *hit.tangent);
+        if frame.bitangent.dot(hit.bitangent) < 0.0 {
+            Self {
+                bitangent: -frame.bitangent,
+                ..frame
+            }
+        } else {
+            frame
+        }
+    }
+
+    /// around the shading normal, flipped to face the incoming ray
+    pub fn facing(hit: &Hit<Vec3>) -> Self {
+        Self::new(hit.facing_normal(), *hit.tangent)
+    }
+
+    pub fn to_local<R: Into<Vec3>>(&self, direction: R) -> Vec3 {
+        let direction: Vec3 = direction.into();
+        Vec3::new(
+            direction.dot(self.tangent),
+            direction.dot(self.bitangent),
+            direction.dot(self.normal),
+        )
+    }
+
+    pub fn to_world(&self, local: Vec3) -> Vec3 {
+        self.tangent * local.x + self.bitangent * local.y + self.normal * local.z
+    }
+}
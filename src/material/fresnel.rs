@@ -0,0 +1,40 @@
+use super::*;
+
+/// The fraction of unpolarized light reflected at the boundary between two dielectrics.
+///
+/// `eta` is the ratio of the index of refraction on the incident side to the one on the other side.
+pub fn dielectric_fresnel(cos_incident: Component, eta: Component) -> Component {
+    let cos_incident = cos_incident.clamp(0.0, 1.0);
+    let sin_transmitted_sq = eta * eta * (1.0 - cos_incident * cos_incident);
+    if sin_transmitted_sq >= 1.0 {
+        return 1.0; // total internal reflection
+    }
+    let cos_transmitted = (1.0 - sin_transmitted_sq).sqrt();
+    let rs = (eta * cos_incident - cos_transmitted) / (eta * cos_incident + cos_transmitted);
+    let rp = (cos_incident - eta * cos_transmitted) / (cos_incident + eta * cos_transmitted);
+    0.5 * (rs * rs + rp * rp)
+}
+
+// see https://seblagarde.wordpress.com/2013/04/29/memo-on-fresnel-equations/
+pub fn conductor_fresnel(cos_incident: Component, eta: Component, k: Component) -> Component {
+    let cos = cos_incident.clamp(0.0, 1.0);
+    let cos_sq = cos * cos;
+    let sin_sq = 1.0 - cos_sq;
+    let t0 = eta * eta - k * k - sin_sq;
+    let a_sq_plus_b_sq = (t0 * t0 + 4.0 * eta * eta * k * k).sqrt();
+    let a = (0.5 * (a_sq_plus_b_sq + t0)).max(0.0).sqrt();
+    let t1 = a_sq_plus_b_sq + cos_sq;
+    let t2 = 2.0 * cos * a;
+    let rs = (t1 - t2) / (t1 + t2);
+    let t3 = cos_sq * a_sq_plus_b_sq + sin_sq * sin_sq;
+    let t4 = t2 * sin_sq;
+    let rp = rs * (t3 - t4) / (t3 + t4);
+    0.5 * (rp + rs)
+}
+
+/// Schlick's approximation, blending from the reflectance at normal incidence to white at grazing angles.
+pub fn schlick_fresnel(cos_incident: Component, normal_reflectance: Color) -> Color {
+    let weight = (1.0 - cos_incident.clamp(0.0, 1.0)).powi(5);
+    let white = Color::white();
+    normal_reflectance * (1.0 - weight) + white * weight
+}
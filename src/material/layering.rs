@@ -0,0 +1,157 @@
+use super::*;
+use rand::*;
+
+/// Blends two materials, randomly picking one of them for each hit according to the weight.
+pub struct MixMaterial<A, B, W = Component> {
+    pub first: A,
+    pub second: B,
+    /// how likely the second material is to be used, in `0.0..=1.0`
+    pub weight: W,
+}
+
+impl<V: Vector, A: Material<V>, B: Material<V>, W: Texture<V>> Material<V>
+    for MixMaterial<A, B, W>
+{
+    fn behavior(&self, hit: Hit<V>) -> Behavior<V> {
+        if thread_rng().gen::<Component>() < self.weight.value_at(&hit) {
+            self.second.behavior(hit)
+        } else {
+            self.first.behavior(hit)
+        }
+    }
+
+    fn evaluate(&self, hit: &Hit<V>, direction: Normalized<V>) -> Color {
+        let weight = self.weight.value_at(hit);
+        self.first.evaluate(hit, direction) * (1.0 - weight)
+            + self.second.evaluate(hit, direction) * weight
+    }
+
+    fn pdf(&self, hit: &Hit<V>, direction: Normalized<V>) -> Component {
+        let weight = self.weight.value_at(hit);
+        self.first.pdf(hit, direction) * (1.0 - weight) + self.second.pdf(hit, direction) * weight
+    }
+
+    fn has_cutouts(&self) -> bool {
+        self.first.has_cutouts() || self.second.has_cutouts()
+    }
+
+    fn opacity(&self, hit: &Hit<V>) -> Component {
+        let weight = self.weight.value_at(hit);
+        self.first.opacity(hit) * (1.0 - weight) + self.second.opacity(hit) * weight
+    }
+}
+
+/// Puts a layer of absorbing dielectric coating (e.g. varnish or clearcoat) on top of another material.
+///
+/// Light that isn't reflected off the coating gets to the base material and is attenuated on its way through the coating and back.
+/// Refraction within the coating and reflections off its underside are neglected.
+pub struct CoatedMaterial<M, T = Color> {
+    pub base: M,
+    /// the color light takes on passing straight through the coating once; white for a clear coating
+    pub color: T,
+    /// scales how much light is absorbed, with paths at grazing angles travelling further through the coating
+    pub thickness: Component,
+    pub roughness: Component,
+    pub ior: Component,
+}
+
+impl<M, T> CoatedMaterial<M, T> {
+    /// how much light makes it through the coating in the given direction
+    fn transmittance(&self, color: Color, cos: Component) -> Color {
+        let exponent = self.thickness / cos.abs().max(1e-3);
+        Color::new(
+            color.red.powf(exponent),
+            color.green.powf(exponent),
+            color.blue.powf(exponent),
+            1.0,
+        )
+    }
+}
+
+impl<M: Material<Vec3>, T: Texture<Vec3>> Material<Vec3> for CoatedMaterial<M, T> {
+    fn behavior(&self, hit: Hit<Vec3>) -> Behavior<Vec3> {
+        let frame = Frame::facing(&hit);
+        let distribution = GgxDistribution::new(self.roughness, 0.0);
+        let outgoing = frame.to_local(-hit.ray_direction);
+        let microfacet_normal = distribution.sample_visible_normal(outgoing);
+        let fresnel = dielectric_fresnel(outgoing.dot(microfacet_normal), 1.0 / self.ior);
+
+        if thread_rng().gen::<Component>() < fresnel {
+            let incoming = reflect(outgoing, microfacet_normal);
+            if incoming.z <= 0.0 {
+                return Behavior {
+                    emission: Color::black(),
+                    color: Color::black(),
+                    next_bounce: None,
+                    lobe: Lobe::Glossy,
+                    is_specular: false,
+                };
+            }
+            let shadowing = distribution.g2(outgoing, incoming) / distribution.g1(outgoing);
+            return Behavior {
+                emission: Color::black(),
+                color: Color::white() * shadowing,
+                next_bounce: Some(hit.next_ray(frame.to_world(incoming))),
+                lobe: Lobe::Glossy,
+                is_specular: false,
+            };
+        }
+
+        let color = self.color.color_at(&hit);
+        let base = self.base.behavior(hit);
+        let outgoing_transmittance = self.transmittance(color, outgoing.z);
+        let incoming_transmittance = base.next_bounce.map_or(Color::white(), |bounce| {
+            let cos = frame.normal.dot(bounce.direction);
+            let exit_fresnel = dielectric_fresnel(cos.abs(), 1.0 / self.ior);
+            self.transmittance(color, cos) * (1.0 - exit_fresnel)
+        });
+        Behavior {
+            emission: base.emission * outgoing_transmittance,
+            color: base.color * outgoing_transmittance * incoming_transmittance,
+            next_bounce: base.next_bounce,
+            lobe: base.lobe,
+            is_specular: base.is_specular,
+        }
+    }
+
+    fn evaluate(&self, hit: &Hit<Vec3>, direction: Normalized<Vec3>) -> Color {
+        let frame = Frame::facing(hit);
+        let distribution = GgxDistribution::new(self.roughness, 0.0);
+        let outgoing = frame.to_local(-hit.ray_direction);
+        let incoming = frame.to_local(direction);
+        let coating = match distribution.reflection(outgoing, incoming) {
+            Some((value, half_vector)) => {
+                Color::white()
+                    * (dielectric_fresnel(outgoing.dot(half_vector), 1.0 / self.ior) * value)
+            }
+            None => Color::black(),
+        };
+
+        let color = self.color.color_at(hit);
+        let entry_fresnel = dielectric_fresnel(outgoing.z, 1.0 / self.ior);
+        let exit_fresnel = dielectric_fresnel(incoming.z.abs(), 1.0 / self.ior);
+        let transmittance = self.transmittance(color, outgoing.z)
+            * self.transmittance(color, incoming.z)
+            * ((1.0 - entry_fresnel) * (1.0 - exit_fresnel));
+        coating + self.base.evaluate(hit, direction) * transmittance
+    }
+
+    /// weighs the lobes by the fresnel term for the macroscopic normal rather than that of a sampled microfacet
+    fn pdf(&self, hit: &Hit<Vec3>, direction: Normalized<Vec3>) -> Component {
+        let frame = Frame::facing(hit);
+        let distribution = GgxDistribution::new(self.roughness, 0.0);
+        let outgoing = frame.to_local(-hit.ray_direction);
+        let incoming = frame.to_local(direction);
+        let fresnel = dielectric_fresnel(outgoing.z, 1.0 / self.ior);
+        distribution.reflection_pdf(outgoing, incoming) * fresnel
+            + self.base.pdf(hit, direction) * (1.0 - fresnel)
+    }
+
+    fn has_cutouts(&self) -> bool {
+        self.base.has_cutouts()
+    }
+
+    fn opacity(&self, hit: &Hit<Vec3>) -> Component {
+        self.base.opacity(hit)
+    }
+}
@@ -0,0 +1,112 @@
+use super::*;
+
+/// The complex index of refraction of a conductor, per color channel.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ConductorIor {
+    pub eta: Color,
+    /// absorption coefficient
+    pub k: Color,
+}
+
+impl ConductorIor {
+    fn rgb(eta: (Component, Component, Component), k: (Component, Component, Component)) -> Self {
+        Self {
+            eta: Color::new(eta.0, eta.1, eta.2, 1.0),
+            k: Color::new(k.0, k.1, k.2, 1.0),
+        }
+    }
+
+    // measured values sampled at roughly 650, 550 and 450 nm
+
+    pub fn gold() -> Self {
+        Self::rgb((0.143, 0.374, 1.442), (3.983, 2.385, 1.603))
+    }
+
+    pub fn copper() -> Self {
+        Self::rgb((0.200, 0.924, 1.102), (3.912, 2.452, 2.142))
+    }
+
+    pub fn aluminium() -> Self {
+        Self::rgb((1.657, 0.880, 0.521), (9.224, 6.270, 4.837))
+    }
+
+    pub fn silver() -> Self {
+        Self::rgb((0.155, 0.117, 0.138), (4.828, 3.122, 2.147))
+    }
+
+    /// the fraction of unpolarized light reflected at the given angle
+    pub fn fresnel(&self, cos_incident: Component) -> Color {
+        let channel = |eta: Component, k: Component| conductor_fresnel(cos_incident, eta, k);
+        Color::new(
+            channel(self.eta.red, self.k.red),
+            channel(self.eta.green, self.k.green),
+            channel(self.eta.blue, self.k.blue),
+            1.0,
+        )
+    }
+}
+
+/// A glossy conductor, reflecting light off a rough surface of GGX-distributed microfacets.
+pub struct MetalMaterial<T = Color, R = Component> {
+    /// multiplies the reflectance given by the index of refraction
+    pub tint: T,
+    /// 0 for a perfect mirror, 1 for very rough
+    pub roughness: R,
+    /// in `0.0..=1.0`, stretching highlights along the surface's tangent
+    pub anisotropy: Component,
+    pub ior: ConductorIor,
+}
+
+impl<T: Texture<Vec3>, R: Texture<Vec3>> Material<Vec3> for MetalMaterial<T, R> {
+    fn behavior(&self, hit: Hit<Vec3>) -> Behavior<Vec3> {
+        let frame = Frame::facing(&hit);
+        let distribution = GgxDistribution::new(self.roughness.value_at(&hit), self.anisotropy);
+
+        let outgoing = frame.to_local(-hit.ray_direction);
+        let microfacet_normal = distribution.sample_visible_normal(outgoing);
+        let incoming = reflect(outgoing, microfacet_normal);
+        if incoming.z <= 0.0 {
+            // reflected into the surface, so it'd be shadowed by other microfacets
+            return Behavior {
+                emission: Color::black(),
+                color: Color::black(),
+                next_bounce: None,
+                lobe: Lobe::Glossy,
+                is_specular: false,
+            };
+        }
+
+        // with visible normal sampling, everything but fresnel and shadowing cancels out
+        let fresnel = self.ior.fresnel(outgoing.dot(microfacet_normal));
+        let shadowing = distribution.g2(outgoing, incoming) / distribution.g1(outgoing);
+
+        Behavior {
+            emission: Color::black(),
+            color: fresnel * self.tint.color_at(&hit) * shadowing,
+            next_bounce: Some(hit.next_ray(frame.to_world(incoming))),
+            lobe: Lobe::Glossy,
+            is_specular: false,
+        }
+    }
+
+    fn evaluate(&self, hit: &Hit<Vec3>, direction: Normalized<Vec3>) -> Color {
+        let frame = Frame::facing(hit);
+        let distribution = GgxDistribution::new(self.roughness.value_at(hit), self.anisotropy);
+        let outgoing = frame.to_local(-hit.ray_direction);
+        match distribution.reflection(outgoing, frame.to_local(direction)) {
+            Some((value, half_vector)) => {
+                self.ior.fresnel(outgoing.dot(half_vector)) * self.tint.color_at(hit) * value
+            }
+            None => Color::black(),
+        }
+    }
+
+    fn pdf(&self, hit: &Hit<Vec3>, direction: Normalized<Vec3>) -> Component {
+        let frame = Frame::facing(hit);
+        let distribution = GgxDistribution::new(self.roughness.value_at(hit), self.anisotropy);
+        distribution.reflection_pdf(
+            frame.to_local(-hit.ray_direction),
+            frame.to_local(direction),
+        )
+    }
+}
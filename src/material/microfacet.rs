@@ -0,0 +1,129 @@
+use super::*;
+use rand::*;
+use std::f32::consts;
+
+/// The GGX (Trowbridge-Reitz) distribution of microfacet normals, in the local space of a `Frame`.
+#[derive(Debug, Copy, Clone)]
+pub struct GgxDistribution {
+    pub alpha_x: Component,
+    pub alpha_y: Component,
+}
+
+impl GgxDistribution {
+    /// Uses the perceptually linear mapping from Disney's principled BRDF.
+    /// `anisotropy` in `0.0..=1.0` stretches highlights along the tangent.
+    pub fn new(roughness: Component, anisotropy: Component) -> Self {
+        let alpha = roughness.clamp(0.0, 1.0).powi(2);
+        let aspect = (1.0 - 0.9 * anisotropy.clamp(0.0, 1.0)).sqrt();
+        Self {
+            // perfectly smooth surfaces would make the math degenerate
+            alpha_x: (alpha / aspect).max(1e-3),
+            alpha_y: (alpha * aspect).max(1e-3),
+        }
+    }
+
+    /// density of microfacets with the given normal
+    pub fn d(&self, normal: Vec3) -> Component {
+        if normal.z <= 0.0 {
+            return 0.0;
+        }
+        let e = (normal.x / self.alpha_x).powi(2)
+            + (normal.y / self.alpha_y).powi(2)
+            + normal.z.powi(2);
+        1.0 / (consts::PI * self.alpha_x * self.alpha_y * e * e)
+    }
+
+    fn lambda(&self, direction: Vec3) -> Component {
+        let projected = (self.alpha_x * direction.x).powi(2) + (self.alpha_y * direction.y).powi(2);
+        let tan_sq = projected / direction.z.powi(2);
+        ((1.0 + tan_sq).sqrt() - 1.0) / 2.0
+    }
+
+    /// Smith masking: the fraction of microfacets visible from the given direction
+    pub fn g1(&self, direction: Vec3) -> Component {
+        1.0 / (1.0 + self.lambda(direction))
+    }
+
+    /// height-correlated Smith masking-shadowing for a pair of directions
+    pub fn g2(&self, outgoing: Vec3, incoming: Vec3) -> Component {
+        1.0 / (1.0 + self.lambda(outgoing) + self.lambda(incoming))
+    }
+
+    /// The reflection BRDF times the cosine of `incoming`, without the fresnel term,
+    /// along with the half vector to evaluate that with; `None` if either direction is below the surface.
+    pub fn reflection(&self, outgoing: Vec3, incoming: Vec3) -> Option<(Component, Vec3)> {
+        if outgoing.z <= 0.0 || incoming.z <= 0.0 {
+            return None;
+        }
+        let half_vector: Vec3 = (outgoing + incoming).normalized().into();
+        let value = self.d(half_vector) * self.g2(outgoing, incoming) / (4.0 * outgoing.z);
+        Some((value, half_vector))
+    }
+
+    /// the pdf of `incoming` when reflecting `outgoing` off a normal from `sample_visible_normal`
+    pub fn reflection_pdf(&self, outgoing: Vec3, incoming: Vec3) -> Component {
+        if outgoing.z <= 0.0 || incoming.z <= 0.0 {
+            return 0.0;
+        }
+        let half_vector: Vec3 = (outgoing + incoming).normalized().into();
+        self.g1(outgoing) * self.d(half_vector) / (4.0 * outgoing.z)
+    }
+
+    /// Samples a microfacet normal among those visible from `outgoing`, following Heitz 2018.
+    pub fn sample_visible_normal(&self, outgoing: Vec3) -> Vec3 {
+        let mut rng = thread_rng();
+        let (u1, u2) = (rng.gen::<Component>(), rng.gen::<Component>());
+
+        // stretch the view direction so the distribution becomes a hemisphere
+        let view = Vec3::new(
+            self.alpha_x * outgoing.x,
+            self.alpha_y * outgoing.y,
+            outgoing.z,
+        )
+        .normalized();
+        let length_sq = view.x * view.x + view.y * view.y;
+        let t1 = if length_sq > 0.0 {
+            Vec3::new(-view.y, view.x, 0.0) / length_sq.sqrt()
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let t2 = (*view).cross(t1);
+
+        // sample the projected half-disk
+        let radius = u1.sqrt();
+        let angle = consts::TAU * u2;
+        let p1 = radius * angle.cos();
+        let s = 0.5 * (1.0 + view.z);
+        let p2 = (1.0 - s) * (1.0 - p1 * p1).sqrt() + s * radius * angle.sin();
+        let p3 = (1.0 - p1 * p1 - p2 * p2).max(0.0).sqrt();
+        let normal = t1 * p1 + t2 * p2 + view * p3;
+
+        // unstretch
+        Vec3::new(
+            self.alpha_x * normal.x,
+            self.alpha_y * normal.y,
+            normal.z.max(1e-6),
+        )
+        .normalized()
+        .into()
+    }
+}
+
+/// `direction` mirrored about `normal`, both pointing away from the surface
+pub fn reflect(direction: Vec3, normal: Vec3) -> Vec3 {
+    normal * (2.0 * direction.dot(normal)) - direction
+}
+
+/// `direction` refracted through a surface with the given normal, both pointing away from it on the incident side.
+///
+/// `eta` is the ratio of the index of refraction on the incident side to the one on the other side.
+/// `None` in case of total internal reflection.
+pub fn refract(direction: Vec3, normal: Vec3, eta: Component) -> Option<Vec3> {
+    let cos_incident = direction.dot(normal);
+    let sin_transmitted_sq = eta * eta * (1.0 - cos_incident * cos_incident);
+    if sin_transmitted_sq >= 1.0 {
+        return None;
+    }
+    let cos_transmitted = (1.0 - sin_transmitted_sq).sqrt();
+    Some(normal * (eta * cos_incident - cos_transmitted) - direction * eta)
+}
@@ -0,0 +1,102 @@
+mod alpha_mask;
+mod basic;
+mod dielectric;
+mod emissive;
+mod frame;
+mod fresnel;
+mod layering;
+mod metal;
+mod microfacet;
+mod normal_mapping;
+mod principled;
+mod rough_diffuse;
+
+pub use alpha_mask::*;
+pub use basic::*;
+pub use dielectric::*;
+pub use emissive::*;
+pub use frame::*;
+pub use fresnel::*;
+pub use layering::*;
+pub use metal::*;
+pub use microfacet::*;
+pub use normal_mapping::*;
+pub use principled::*;
+pub use rough_diffuse::*;
+
+use super::*;
+use std::sync::Arc;
+
+/// The kind of scattering a bounce comes from, which the tracer limits separately.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Lobe {
+    Diffuse,
+    /// including perfectly specular reflections
+    Glossy,
+    /// passing through the surface, whether refracted or not
+    Transmission,
+}
+
+pub struct Behavior<V: Vector> {
+    pub emission: Color,
+    pub color: Color,
+    pub next_bounce: Option<Ray<V>>,
+    /// how `next_bounce` was scattered
+    pub lobe: Lobe,
+    /// whether `next_bounce` is the only direction the lobe scatters in, like a mirror's,
+    /// so light arriving from it can't be found by sampling the lights
+    pub is_specular: bool,
+}
+
+pub trait Material<V: Vector>: 'static + Send + Sync {
+    fn behavior(&self, hit: Hit<V>) -> Behavior<V>;
+
+    /// How much of the light arriving from `direction` is scattered back along the hit's ray,
+    /// i.e. the BSDF times the cosine between `direction` and the normal.
+    ///
+    /// Used to shade hits with light from known directions;
+    /// perfectly specular materials only scatter light in a single direction, so they leave this black.
+    fn evaluate(&self, _hit: &Hit<V>, _direction: Normalized<V>) -> Color {
+        Color::black()
+    }
+
+    /// The pdf per unit solid angle of `behavior` bouncing towards `direction`, for weighing it against sampling lights.
+    ///
+    /// An approximation is fine, as long as it's only positive where `behavior` can bounce
+    /// and positive wherever `evaluate` is, so materials implementing one have to implement the other.
+    fn pdf(&self, _hit: &Hit<V>, _direction: Normalized<V>) -> Component {
+        0.0
+    }
+
+    /// whether `opacity` may ever let rays pass through the surface, so opaque materials can skip checking it
+    fn has_cutouts(&self) -> bool {
+        false
+    }
+
+    /// how likely rays are to hit the surface at this point rather than pass through it, in `0.0..=1.0`
+    fn opacity(&self, _hit: &Hit<V>) -> Component {
+        1.0
+    }
+}
+
+impl<V: Vector, M: Material<V> + ?Sized> Material<V> for Arc<M> {
+    fn behavior(&self, hit: Hit<V>) -> Behavior<V> {
+        (**self).behavior(hit)
+    }
+
+    fn evaluate(&self, hit: &Hit<V>, direction: Normalized<V>) -> Color {
+        (**self).evaluate(hit, direction)
+    }
+
+    fn pdf(&self, hit: &Hit<V>, direction: Normalized<V>) -> Component {
+        (**self).pdf(hit, direction)
+    }
+
+    fn has_cutouts(&self) -> bool {
+        (**self).has_cutouts()
+    }
+
+    fn opacity(&self, hit: &Hit<V>) -> Component {
+        (**self).opacity(hit)
+    }
+}
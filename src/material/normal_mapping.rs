@@ -0,0 +1,102 @@
+use super::*;
+
+/// Perturbs the shading normal according to a tangent-space normal map, then lets another material take over.
+///
+/// Colors map to directions as exported by most tools: red along the tangent, green along the bitangent and blue along the normal,
+/// each stretched from `0.0..=1.0` to `-1.0..=1.0`.
+pub struct NormalMappedMaterial<M, T> {
+    pub material: M,
+    pub normal_map: T,
+}
+
+impl<M, T: Texture<Vec3>> NormalMappedMaterial<M, T> {
+    fn perturbed(&self, hit: &Hit<Vec3>) -> Hit<Vec3> {
+        let color = self.normal_map.color_at(hit);
+        let local = Vec3::new(color.red, color.green, color.blue) * 2.0 - Vec3::uniform(1.0);
+        let normal = Frame::from_hit(hit).to_world(local).normalized();
+        Hit { normal, ..*hit }
+    }
+}
+
+impl<M: Material<Vec3>, T: Texture<Vec3>> Material<Vec3> for NormalMappedMaterial<M, T> {
+    fn behavior(&self, hit: Hit<Vec3>) -> Behavior<Vec3> {
+        self.material.behavior(self.perturbed(&hit))
+    }
+
+    fn evaluate(&self, hit: &Hit<Vec3>, direction: Normalized<Vec3>) -> Color {
+        self.material.evaluate(&self.perturbed(hit), direction)
+    }
+
+    fn pdf(&self, hit: &Hit<Vec3>, direction: Normalized<Vec3>) -> Component {
+        self.material.pdf(&self.perturbed(hit), direction)
+    }
+
+    fn has_cutouts(&self) -> bool {
+        self.material.has_cutouts()
+    }
+
+    fn opacity(&self, hit: &Hit<Vec3>) -> Component {
+        self.material.opacity(hit)
+    }
+}
+
+/// how far to step along the surface when estimating the slope of a height texture
+const BUMP_DELTA: Component = 1e-3;
+
+/// Perturbs the shading normal according to the slopes of a height texture, then lets another material take over.
+pub struct BumpMappedMaterial<M, T> {
+    pub material: M,
+    pub height: T,
+    /// scales the slopes, measured per unit of whatever the texture varies with (texture coordinates or position)
+    pub strength: Component,
+}
+
+impl<M, T: Texture<Vec3>> BumpMappedMaterial<M, T> {
+    fn perturbed(&self, hit: &Hit<Vec3>) -> Hit<Vec3> {
+        // step in texture space and along the surface at once, so it works for either kind of texture
+        let height_at = |du: Component, dv: Component| {
+            let shifted = Hit {
+                uv: hit.uv + Vec2::new(du, dv),
+                intersection: hit.intersection + hit.tangent * du + hit.bitangent * dv,
+                ..*hit
+            };
+            self.height.value_at(&shifted)
+        };
+        let height = height_at(0.0, 0.0);
+        let slope_u = (height_at(BUMP_DELTA, 0.0) - height) / BUMP_DELTA;
+        let slope_v = (height_at(0.0, BUMP_DELTA) - height) / BUMP_DELTA;
+
+        let frame = Frame::from_hit(hit);
+        let normal = frame.to_world(Vec3::new(
+            -slope_u * self.strength,
+            -slope_v * self.strength,
+            1.0,
+        ));
+        Hit {
+            normal: normal.normalized(),
+            ..*hit
+        }
+    }
+}
+
+impl<M: Material<Vec3>, T: Texture<Vec3>> Material<Vec3> for BumpMappedMaterial<M, T> {
+    fn behavior(&self, hit: Hit<Vec3>) -> Behavior<Vec3> {
+        self.material.behavior(self.perturbed(&hit))
+    }
+
+    fn evaluate(&self, hit: &Hit<Vec3>, direction: Normalized<Vec3>) -> Color {
+        self.material.evaluate(&self.perturbed(hit), direction)
+    }
+
+    fn pdf(&self, hit: &Hit<Vec3>, direction: Normalized<Vec3>) -> Component {
+        self.material.pdf(&self.perturbed(hit), direction)
+    }
+
+    fn has_cutouts(&self) -> bool {
+        self.material.has_cutouts()
+    }
+
+    fn opacity(&self, hit: &Hit<Vec3>) -> Component {
+        self.material.opacity(hit)
+    }
+}
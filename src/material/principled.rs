@@ -0,0 +1,338 @@
+use super::*;
+use rand::*;
+use std::f32::consts;
+
+/// A physically based "uber" material along the lines of Disney's principled BSDF and glTF's metallic-roughness model.
+///
+/// It stacks an optional clearcoat over a blend of metal and dielectric;
+/// light not reflected by the dielectric's specular layer is either transmitted or scattered diffusely.
+/// Each bounce randomly picks one of those lobes by the fresnel term for the macroscopic normal,
+/// leaving the one for the sampled microfacet to its color.
+pub struct PrincipledMaterial<C = Color, M = Component, R = Component, E = Color> {
+    pub base_color: C,
+    /// 0 for dielectrics, 1 for metals
+    pub metallic: M,
+    pub roughness: R,
+    /// scales the dielectric's specular reflectance, with 0.5 matching its index of refraction
+    pub specular: Component,
+    pub anisotropy: Component,
+    /// a soft highlight at grazing angles, for cloth
+    pub sheen: Component,
+    /// how much the sheen takes on the base color rather than staying white
+    pub sheen_tint: Component,
+    pub clearcoat: Component,
+    pub clearcoat_roughness: Component,
+    /// 0 for opaque, 1 for fully transmissive dielectrics
+    pub transmission: Component,
+    pub ior: Component,
+    pub emission: E,
+}
+
+impl<C> PrincipledMaterial<C> {
+    /// a rough dielectric, with everything else turned off
+    pub fn new(base_color: C) -> Self {
+        Self {
+            base_color,
+            metallic: 0.0,
+            roughness: 0.5,
+            specular: 0.5,
+            anisotropy: 0.0,
+            sheen: 0.0,
+            sheen_tint: 0.5,
+            clearcoat: 0.0,
+            clearcoat_roughness: 0.03,
+            transmission: 0.0,
+            ior: 1.5,
+            emission: Color::black(),
+        }
+    }
+}
+
+impl<C, M, R, E> Material<Vec3> for PrincipledMaterial<C, M, R, E>
+where
+    C: Texture<Vec3>,
+    M: Texture<Vec3>,
+    R: Texture<Vec3>,
+    E: Texture<Vec3>,
+{
+    fn behavior(&self, hit: Hit<Vec3>) -> Behavior<Vec3> {
+        let emission = self.emission.color_at(&hit);
+        let base_color = self.base_color.color_at(&hit);
+        let metallic = self.metallic.value_at(&hit).clamp(0.0, 1.0);
+        let roughness = self.roughness.value_at(&hit);
+
+        let frame = Frame::facing(&hit);
+        let outgoing = frame.to_local(-hit.ray_direction);
+        let mut rng = thread_rng();
+
+        let reflection = |distribution: GgxDistribution, microfacet_normal: Vec3, tint: Color| {
+            let incoming = reflect(outgoing, microfacet_normal);
+            if incoming.z <= 0.0 {
+                return Behavior {
+                    emission,
+                    color: Color::black(),
+                    next_bounce: None,
+                    lobe: Lobe::Glossy,
+                    is_specular: false,
+                };
+            }
+            let shadowing = distribution.g2(outgoing, incoming) / distribution.g1(outgoing);
+            Behavior {
+                emission,
+                color: tint * shadowing,
+                next_bounce: Some(hit.next_ray(frame.to_world(incoming))),
+                lobe: Lobe::Glossy,
+                is_specular: false,
+            }
+        };
+
+        let eta = if hit.is_back_face() {
+            self.ior
+        } else {
+            1.0 / self.ior
+        };
+
+        // clearcoat
+        if self.clearcoat > 0.0 {
+            let coat_weight = self.clearcoat * dielectric_fresnel(outgoing.z, 1.0 / 1.5);
+            if rng.gen::<Component>() < coat_weight {
+                let distribution = GgxDistribution::new(self.clearcoat_roughness, 0.0);
+                let microfacet_normal = distribution.sample_visible_normal(outgoing);
+                let fresnel = dielectric_fresnel(outgoing.dot(microfacet_normal), 1.0 / 1.5);
+                let tint = Color::white() * (self.clearcoat * fresnel / coat_weight);
+                return reflection(distribution, microfacet_normal, tint);
+            }
+        }
+
+        let distribution = GgxDistribution::new(roughness, self.anisotropy);
+        let microfacet_normal = distribution.sample_visible_normal(outgoing);
+        let cos_microfacet = outgoing.dot(microfacet_normal);
+
+        // metal
+        if rng.gen::<Component>() < metallic {
+            let fresnel = schlick_fresnel(cos_microfacet, base_color);
+            return reflection(distribution, microfacet_normal, fresnel);
+        }
+
+        // dielectric specular
+        let specular_weight = self.specular_weight(outgoing.z, eta);
+        if rng.gen::<Component>() < specular_weight {
+            let tint =
+                Color::white() * (self.specular_weight(cos_microfacet, eta) / specular_weight);
+            return reflection(distribution, microfacet_normal, tint);
+        }
+
+        // transmission
+        if rng.gen::<Component>() < self.transmission {
+            return match refract(outgoing, microfacet_normal, eta) {
+                Some(incoming) if incoming.z < 0.0 => {
+                    let shadowing = distribution.g2(outgoing, incoming) / distribution.g1(outgoing);
+                    Behavior {
+                        emission,
+                        color: base_color * shadowing,
+                        next_bounce: Some(hit.next_ray(frame.to_world(incoming))),
+                        lobe: Lobe::Transmission,
+                        is_specular: false,
+                    }
+                }
+                // total internal reflection
+                _ => reflection(distribution, microfacet_normal, Color::white()),
+            };
+        }
+
+        // diffuse, with sheen
+        let incoming = cosine_weighted_hemisphere();
+        Behavior {
+            emission,
+            color: self.diffuse(base_color, outgoing, incoming),
+            next_bounce: Some(hit.next_ray(frame.to_world(incoming))),
+            lobe: Lobe::Diffuse,
+            is_specular: false,
+        }
+    }
+
+    /// Weighs the lobes just like `behavior` picks them, leaving out transmission
+    /// apart from the total internal reflection it falls back to.
+    fn evaluate(&self, hit: &Hit<Vec3>, direction: Normalized<Vec3>) -> Color {
+        let frame = Frame::facing(hit);
+        let outgoing = frame.to_local(-hit.ray_direction);
+        let incoming = frame.to_local(direction);
+        let distribution = GgxDistribution::new(self.roughness.value_at(hit), self.anisotropy);
+        let (value, half_vector) = match distribution.reflection(outgoing, incoming) {
+            Some(reflection) => reflection,
+            None => return Color::black(),
+        };
+        let cos_half = outgoing.dot(half_vector);
+
+        let base_color = self.base_color.color_at(hit);
+        let metallic = self.metallic.value_at(hit).clamp(0.0, 1.0);
+        let eta = if hit.is_back_face() {
+            self.ior
+        } else {
+            1.0 / self.ior
+        };
+
+        let metal = schlick_fresnel(cos_half, base_color) * value;
+        let specular_weight = self.specular_weight(outgoing.z, eta);
+        let reflected = self.specular_weight(cos_half, eta)
+            + (1.0 - specular_weight)
+                * self.transmission
+                * self.internal_reflection(outgoing, half_vector, eta);
+        let specular = Color::white() * (reflected * value);
+        let diffuse = self.diffuse(base_color, outgoing, incoming) * (incoming.z / consts::PI);
+        let diffuse_weight = (1.0 - specular_weight) * (1.0 - self.transmission);
+        let layers = metal * metallic + (specular + diffuse * diffuse_weight) * (1.0 - metallic);
+
+        if self.clearcoat <= 0.0 {
+            return layers;
+        }
+        let coat_distribution = GgxDistribution::new(self.clearcoat_roughness, 0.0);
+        let coat = coat_distribution
+            .reflection(outgoing, incoming)
+            .map_or(0.0, |(value, half_vector)| {
+                dielectric_fresnel(outgoing.dot(half_vector), 1.0 / 1.5) * value
+            });
+        let coat_weight = self.clearcoat * dielectric_fresnel(outgoing.z, 1.0 / 1.5);
+        Color::white() * (self.clearcoat * coat) + layers * (1.0 - coat_weight)
+    }
+
+    /// Picks the lobes like `evaluate` weighs them, so it leaves out transmission as well.
+    fn pdf(&self, hit: &Hit<Vec3>, direction: Normalized<Vec3>) -> Component {
+        let frame = Frame::facing(hit);
+        let outgoing = frame.to_local(-hit.ray_direction);
+        let incoming = frame.to_local(direction);
+        if outgoing.z <= 0.0 || incoming.z <= 0.0 {
+            return 0.0;
+        }
+        let distribution = GgxDistribution::new(self.roughness.value_at(hit), self.anisotropy);
+        let metallic = self.metallic.value_at(hit).clamp(0.0, 1.0);
+        let eta = if hit.is_back_face() {
+            self.ior
+        } else {
+            1.0 / self.ior
+        };
+
+        let half_vector: Vec3 = (outgoing + incoming).normalized().into();
+        let specular = distribution.reflection_pdf(outgoing, incoming);
+        let diffuse = incoming.z / consts::PI;
+        let specular_weight = self.specular_weight(outgoing.z, eta);
+        let internal_reflection = self.internal_reflection(outgoing, half_vector, eta);
+        let dielectric = specular
+            * (specular_weight + (1.0 - specular_weight) * self.transmission * internal_reflection)
+            + diffuse * (1.0 - specular_weight) * (1.0 - self.transmission);
+        let layers = specular * metallic + dielectric * (1.0 - metallic);
+
+        if self.clearcoat <= 0.0 {
+            return layers;
+        }
+        let coat_distribution = GgxDistribution::new(self.clearcoat_roughness, 0.0);
+        let coat_weight = self.clearcoat * dielectric_fresnel(outgoing.z, 1.0 / 1.5);
+        coat_distribution.reflection_pdf(outgoing, incoming) * coat_weight
+            + layers * (1.0 - coat_weight)
+    }
+}
+
+impl<C, M, R, E> PrincipledMaterial<C, M, R, E> {
+    /// how likely the dielectric's specular layer reflects light arriving at the given angle
+    fn specular_weight(&self, cos_incident: Component, eta: Component) -> Component {
+        (dielectric_fresnel(cos_incident, eta) * self.specular * 2.0).min(1.0)
+    }
+
+    /// 1 if transmission through the given microfacet falls back to reflecting off it, 0 otherwise
+    fn internal_reflection(
+        &self,
+        outgoing: Vec3,
+        microfacet_normal: Vec3,
+        eta: Component,
+    ) -> Component {
+        match refract(outgoing, microfacet_normal, eta) {
+            Some(incoming) if incoming.z < 0.0 => 0.0,
+            _ => 1.0,
+        }
+    }
+
+    /// the diffuse reflectance including sheen, i.e. the BRDF times π
+    fn diffuse(&self, base_color: Color, outgoing: Vec3, incoming: Vec3) -> Color {
+        let half_vector = (outgoing + incoming).normalized();
+        let sheen_color = Color::white() * (1.0 - self.sheen_tint) + base_color * self.sheen_tint;
+        let sheen_weight = (1.0 - incoming.dot(half_vector)).clamp(0.0, 1.0).powi(5);
+        base_color + sheen_color * (self.sheen * sheen_weight)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(cos_outgoing: Component) -> Hit<Vec3> {
+        let sin_outgoing = (1.0 - cos_outgoing * cos_outgoing).sqrt();
+        Hit {
+            ray_direction: Vec3::new(-sin_outgoing, 0.0, -cos_outgoing).normalized(),
+            intersection: Vec3::new(0.0, 0.0, 0.0),
+            normal: Vec3::new(0.0, 0.0, 1.0).normalized(),
+            geometric_normal: Vec3::new(0.0, 0.0, 1.0).normalized(),
+            uv: Vec2::new(0.0, 0.0),
+            tangent: Vec3::new(1.0, 0.0, 0.0).normalized(),
+            bitangent: Vec3::new(0.0, 1.0, 0.0).normalized(),
+        }
+    }
+
+    /// the reflected albedo and the probability of reflecting, first as sampled by `behavior`,
+    /// then by integrating `evaluate` and `pdf` over the hemisphere
+    fn albedos(material: &PrincipledMaterial, hit: &Hit<Vec3>) -> [(Component, Component); 2] {
+        const SAMPLES: usize = 200_000;
+        let mut sampled = (0.0, 0.0);
+        let mut integrated = (0.0, 0.0);
+        for _ in 0..SAMPLES {
+            let behavior = material.behavior(*hit);
+            if let Some(ray) = behavior.next_bounce {
+                if ray.direction.z > 0.0 {
+                    sampled.0 += behavior.color.luminance();
+                    sampled.1 += 1.0;
+                }
+            }
+
+            let direction = uniform_hemisphere().normalized();
+            let weight = 2.0 * consts::PI;
+            integrated.0 += material.evaluate(hit, direction).luminance() * weight;
+            integrated.1 += material.pdf(hit, direction) * weight;
+        }
+        let average = |(albedo, probability): (Component, Component)| {
+            (
+                albedo / SAMPLES as Component,
+                probability / SAMPLES as Component,
+            )
+        };
+        [average(sampled), average(integrated)]
+    }
+
+    #[test]
+    fn evaluate_and_pdf_match_behavior() {
+        let mut coated = PrincipledMaterial::new(Color::new(0.8, 0.4, 0.2, 1.0));
+        coated.clearcoat = 1.0;
+        coated.clearcoat_roughness = 0.3;
+        let mut glassy = PrincipledMaterial::new(Color::new(0.2, 0.6, 0.9, 1.0));
+        glassy.specular = 0.8;
+        glassy.transmission = 0.5;
+        let mut metallic = PrincipledMaterial::new(Color::new(0.9, 0.7, 0.3, 1.0));
+        metallic.metallic = 0.5;
+        metallic.sheen = 0.5;
+
+        for material in &[coated, glassy, metallic] {
+            for &cos_outgoing in &[0.1, 0.2, 0.5, 0.9] {
+                let [sampled, integrated] = albedos(material, &hit(cos_outgoing));
+                for &(sampled, integrated) in
+                    &[(sampled.0, integrated.0), (sampled.1, integrated.1)]
+                {
+                    assert!(
+                        (sampled - integrated).abs() < 0.02 + 0.03 * integrated,
+                        "sampled {} but integrated {} at cos {}",
+                        sampled,
+                        integrated,
+                        cos_outgoing
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use super::*;
+use rand::*;
+use std::f32::consts;
+
+/// A diffuse material whose surface is made up of tiny Lambertian facets, following Oren and Nayar.
+///
+/// Rough surfaces like clay or concrete look flatter than Lambertian ones and reflect more light back towards its source.
+pub struct OrenNayarMaterial<T = Color> {
+    pub color: T,
+    /// standard deviation of the facet angles in radians; 0 is Lambertian
+    pub roughness: Component,
+}
+
+impl<T> OrenNayarMaterial<T> {
+    /// the reflectance relative to a Lambertian surface's, for directions in local space
+    fn factor(&self, outgoing: Vec3, incoming: Vec3) -> Component {
+        let sigma_sq = self.roughness * self.roughness;
+        let a = 1.0 - 0.5 * sigma_sq / (sigma_sq + 0.33);
+        let b = 0.45 * sigma_sq / (sigma_sq + 0.09);
+
+        let sin_outgoing = (1.0 - outgoing.z * outgoing.z).max(0.0).sqrt();
+        let sin_incoming = (1.0 - incoming.z * incoming.z).max(0.0).sqrt();
+        let cos_azimuth = if sin_outgoing > 1e-4 && sin_incoming > 1e-4 {
+            (outgoing.x * incoming.x + outgoing.y * incoming.y) / (sin_outgoing * sin_incoming)
+        } else {
+            0.0
+        };
+
+        // sin(max(θo, θi)) * tan(min(θo, θi))
+        let (sin_alpha, tan_beta) = if incoming.z < outgoing.z {
+            (sin_incoming, sin_outgoing / outgoing.z)
+        } else {
+            (sin_outgoing, sin_incoming / incoming.z)
+        };
+        a + b * cos_azimuth.max(0.0) * sin_alpha * tan_beta
+    }
+}
+
+impl<T: Texture<Vec3>> Material<Vec3> for OrenNayarMaterial<T> {
+    fn behavior(&self, hit: Hit<Vec3>) -> Behavior<Vec3> {
+        let frame = Frame::facing(&hit);
+        let outgoing = frame.to_local(-hit.ray_direction);
+        let incoming = cosine_weighted_hemisphere();
+        // the cosine and 1/π cancel out with the pdf
+        Behavior {
+            emission: Color::black(),
+            color: self.color.color_at(&hit) * self.factor(outgoing, incoming),
+            next_bounce: Some(hit.next_ray(frame.to_world(incoming))),
+            lobe: Lobe::Diffuse,
+            is_specular: false,
+        }
+    }
+
+    fn evaluate(&self, hit: &Hit<Vec3>, direction: Normalized<Vec3>) -> Color {
+        let frame = Frame::facing(hit);
+        let outgoing = frame.to_local(-hit.ray_direction);
+        let incoming = frame.to_local(direction);
+        if incoming.z <= 0.0 {
+            return Color::black();
+        }
+        self.color.color_at(hit) * (self.factor(outgoing, incoming) * incoming.z / consts::PI)
+    }
+
+    fn pdf(&self, hit: &Hit<Vec3>, direction: Normalized<Vec3>) -> Component {
+        hit.facing_normal().dot(direction).max(0.0) / consts::PI
+    }
+}
+
+/// Cloth like velvet or satin: a Lambertian base under a sheen that brightens towards grazing angles,
+/// using the "Charlie" distribution by Estevez and Kulla.
+pub struct VelvetMaterial<T = Color, S = Color> {
+    pub color: T,
+    pub sheen_color: S,
+    /// in `0.0..=1.0`, with rougher sheens spreading out further from the silhouette
+    pub roughness: Component,
+}
+
+impl<T, S> VelvetMaterial<T, S> {
+    /// the sheen BRDF for directions in local space
+    fn sheen(&self, outgoing: Vec3, incoming: Vec3) -> Component {
+        let alpha = self.roughness.clamp(1e-3, 1.0);
+        let half_vector = (outgoing + incoming).normalized();
+        let sin_half = (1.0 - half_vector.z * half_vector.z).max(0.0).sqrt();
+        let distribution = (2.0 + 1.0 / alpha) * sin_half.powf(1.0 / alpha) / consts::TAU;
+        // Neubelt and Pettineo's visibility term
+        let visibility =
+            1.0 / (4.0 * (incoming.z + outgoing.z - incoming.z * outgoing.z)).max(1e-4);
+        distribution * visibility
+    }
+
+    /// the pdf of sampling a direction at the given cosine from the normal, for the even mix of cosine-weighted and uniform hemispheres
+    fn mixture_pdf(cos: Component) -> Component {
+        0.5 * cos / consts::PI + 0.5 / consts::TAU
+    }
+
+    fn brdf(&self, hit: &Hit<Vec3>, outgoing: Vec3, incoming: Vec3) -> Color
+    where
+        T: Texture<Vec3>,
+        S: Texture<Vec3>,
+    {
+        let diffuse = self.color.color_at(hit) / consts::PI;
+        diffuse + self.sheen_color.color_at(hit) * self.sheen(outgoing, incoming)
+    }
+}
+
+impl<T: Texture<Vec3>, S: Texture<Vec3>> Material<Vec3> for VelvetMaterial<T, S> {
+    fn behavior(&self, hit: Hit<Vec3>) -> Behavior<Vec3> {
+        let frame = Frame::facing(&hit);
+        let outgoing = frame.to_local(-hit.ray_direction);
+
+        // the base prefers directions near the normal, the sheen grazing ones, so sample from an even mix of both
+        let incoming = if thread_rng().gen::<bool>() {
+            cosine_weighted_hemisphere()
+        } else {
+            uniform_hemisphere()
+        };
+        let pdf = Self::mixture_pdf(incoming.z);
+
+        Behavior {
+            emission: Color::black(),
+            color: self.brdf(&hit, outgoing, incoming) * (incoming.z / pdf),
+            next_bounce: Some(hit.next_ray(frame.to_world(incoming))),
+            lobe: Lobe::Diffuse,
+            is_specular: false,
+        }
+    }
+
+    fn evaluate(&self, hit: &Hit<Vec3>, direction: Normalized<Vec3>) -> Color {
+        let frame = Frame::facing(hit);
+        let outgoing = frame.to_local(-hit.ray_direction);
+        let incoming = frame.to_local(direction);
+        if incoming.z <= 0.0 {
+            return Color::black();
+        }
+        self.brdf(hit, outgoing, incoming) * incoming.z
+    }
+
+    fn pdf(&self, hit: &Hit<Vec3>, direction: Normalized<Vec3>) -> Component {
+        let cos = hit.facing_normal().dot(direction);
+        if cos <= 0.0 {
+            return 0.0;
+        }
+        Self::mixture_pdf(cos)
+    }
+}
@@ -0,0 +1,312 @@
+use super::*;
+use rand::*;
+
+/// A single triangle, with texture coordinates running along its edges from `a` to `b` and from `a` to `c`.
+///
+/// Its normal faces the side from which the corners go around counter-clockwise.
+#[derive(Debug, Copy, Clone)]
+pub struct Triangle {
+    pub a: Vec3,
+    pub b: Vec3,
+    pub c: Vec3,
+}
+
+impl Triangle {
+    pub fn normal(&self) -> Normalized<Vec3> {
+        (self.c - self.a).cross(self.b - self.a).normalized()
+    }
+
+    /// Distance and the barycentric weights of `b` and `c` at the hit, following Möller and Trumbore.
+    fn hit(&self, ray: &Ray<Vec3>, near_clipping: Component) -> Option<(Component, Vec2)> {
+        let edge_b = self.b - self.a;
+        let edge_c = self.c - self.a;
+        let p = (*ray.direction).cross(edge_c);
+        let determinant = edge_b.dot(p);
+        if determinant.abs() < 1e-12 {
+            return None; // parallel to the plane
+        }
+        let inverse = 1.0 / determinant;
+        let offset = ray.origin - self.a;
+        let u = offset.dot(p) * inverse;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = offset.cross(edge_b);
+        let v = ray.direction.dot(q) * inverse;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let distance = edge_c.dot(q) * inverse;
+        if distance < near_clipping {
+            return None;
+        }
+        Some((distance, Vec2::new(u, v)))
+    }
+
+    fn padded_bounds(&self) -> Option<BoundingBox<Vec3>> {
+        // pad the box so it doesn't end up infinitely thin
+        let padding = Vec3::uniform(1e-4);
+        BoundingBox::around_points([self.a, self.b, self.c].iter().copied())
+            .map(|b| BoundingBox::new(b.min - padding, b.max + padding))
+    }
+}
+
+impl Shape for Triangle {
+    type V = Vec3;
+
+    fn first_intersection(
+        &self,
+        ray: &Ray<Vec3>,
+        near_clipping: Component,
+    ) -> Option<Intersection<Hit<Vec3>>> {
+        self.hit(ray, near_clipping).map(|(distance, uv)| {
+            let normal = self.normal();
+            Intersection {
+                distance,
+                data: Hit {
+                    ray_direction: ray.direction,
+                    intersection: ray.at(distance),
+                    normal,
+                    geometric_normal: normal,
+                    uv,
+                    tangent: (self.b - self.a).normalized(),
+                    bitangent: (self.c - self.a).normalized(),
+                },
+            }
+        })
+    }
+
+    fn occluded(&self, ray: &Ray<Vec3>, near_clipping: Component, far_clipping: Component) -> bool {
+        self.hit(ray, near_clipping)
+            .is_some_and(|(distance, _)| distance < far_clipping)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox<Vec3>> {
+        self.padded_bounds()
+    }
+}
+
+impl SampleableShape for Triangle {
+    fn area(&self) -> Component {
+        (self.b - self.a).cross(self.c - self.a).norm() / 2.0
+    }
+
+    fn sample_area(&self) -> ShapeSample<Vec3> {
+        let (u, v) = uniform_triangle();
+        ShapeSample {
+            position: self.a + (self.b - self.a) * u + (self.c - self.a) * v,
+            normal: self.normal(),
+            pdf: 1.0 / self.area(),
+        }
+    }
+
+    fn flat_normal(&self) -> Option<Normalized<Vec3>> {
+        Some(self.normal())
+    }
+}
+
+/// Triangles sharing a list of vertices, optionally with normals to interpolate for smooth shading and texture coordinates.
+///
+/// Rays are tested against each triangle in turn,
+/// so large meshes are better split into separate triangles and put into a `BvhScene`.
+pub struct TriangleMesh {
+    positions: Vec<Vec3>,
+    normals: Option<Vec<Normalized<Vec3>>>,
+    uvs: Option<Vec<Vec2>>,
+    /// indices into the vertex lists, counter-clockwise as seen from the front
+    indices: Vec<[usize; 3]>,
+    /// running totals of the triangles' areas, for picking one in proportion to its area
+    cumulative_areas: Vec<Component>,
+    bounds: Option<BoundingBox<Vec3>>,
+}
+
+impl TriangleMesh {
+    /// panics if any index is out of bounds
+    pub fn new(positions: Vec<Vec3>, indices: Vec<[usize; 3]>) -> Self {
+        assert!(!indices.is_empty(), "mesh without triangles");
+        assert!(
+            indices.iter().flatten().all(|&i| i < positions.len()),
+            "triangle index out of bounds"
+        );
+        let mut total = 0.0;
+        let cumulative_areas = indices
+            .iter()
+            .map(|&[a, b, c]| {
+                total += Triangle {
+                    a: positions[a],
+                    b: positions[b],
+                    c: positions[c],
+                }
+                .area();
+                total
+            })
+            .collect();
+        let mut mesh = Self {
+            positions,
+            normals: None,
+            uvs: None,
+            indices,
+            cumulative_areas,
+            bounds: None,
+        };
+        mesh.bounds = mesh
+            .triangles()
+            .filter_map(|t| t.padded_bounds())
+            .reduce(|l, r| l.union(&r));
+        mesh
+    }
+
+    /// panics unless there's a normal for each vertex
+    pub fn with_normals(mut self, normals: Vec<Vec3>) -> Self {
+        assert_eq!(normals.len(), self.positions.len(), "one normal per vertex");
+        self.normals = Some(normals.into_iter().map(|n| n.normalized()).collect());
+        self
+    }
+
+    /// panics unless there are texture coordinates for each vertex
+    pub fn with_uvs(mut self, uvs: Vec<Vec2>) -> Self {
+        assert_eq!(uvs.len(), self.positions.len(), "one uv per vertex");
+        self.uvs = Some(uvs);
+        self
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn triangle(&self, index: usize) -> Triangle {
+        let [a, b, c] = self.indices[index];
+        Triangle {
+            a: self.positions[a],
+            b: self.positions[b],
+            c: self.positions[c],
+        }
+    }
+
+    /// each triangle as a shape of its own, e.g. to build a `BvhScene` over them
+    pub fn triangles(&self) -> impl Iterator<Item = Triangle> + '_ {
+        (0..self.triangle_count()).map(move |i| self.triangle(i))
+    }
+
+    /// the closest triangle hit, with its index and the barycentric weights of its second and third vertex
+    fn hit(&self, ray: &Ray<Vec3>, near_clipping: Component) -> Option<(Component, usize, Vec2)> {
+        let mut closest: Option<(Component, usize, Vec2)> = None;
+        count_intersection_tests(self.triangle_count());
+        for index in 0..self.triangle_count() {
+            if let Some((distance, weights)) = self.triangle(index).hit(ray, near_clipping) {
+                if closest.is_none_or(|(d, _, _)| distance < d) {
+                    closest = Some((distance, index, weights));
+                }
+            }
+        }
+        closest
+    }
+
+    /// the hit on the given triangle, interpolating the vertex attributes
+    fn surface_hit(
+        &self,
+        ray: &Ray<Vec3>,
+        distance: Component,
+        index: usize,
+        weights: Vec2,
+    ) -> Hit<Vec3> {
+        let [a, b, c] = self.indices[index];
+        let interpolate = |values: [Vec3; 3]| {
+            values[0] * (1.0 - weights.x - weights.y)
+                + values[1] * weights.x
+                + values[2] * weights.y
+        };
+
+        let triangle = self.triangle(index);
+        let geometric_normal = triangle.normal();
+        let normal = match &self.normals {
+            Some(normals) => interpolate([*normals[a], *normals[b], *normals[c]]).normalized(),
+            None => geometric_normal,
+        };
+
+        let (uv, tangent, bitangent) = match &self.uvs {
+            Some(uvs) => {
+                let (uv_a, uv_b, uv_c) = (uvs[a], uvs[b], uvs[c]);
+                let uv = uv_a * (1.0 - weights.x - weights.y) + uv_b * weights.x + uv_c * weights.y;
+                // solve for the directions in which u and v increase
+                let (edge_b, edge_c) = (triangle.b - triangle.a, triangle.c - triangle.a);
+                let (delta_b, delta_c) = (uv_b - uv_a, uv_c - uv_a);
+                let determinant = delta_b.x * delta_c.y - delta_b.y * delta_c.x;
+                if determinant.abs() > 1e-12 {
+                    let tangent = (edge_b * delta_c.y - edge_c * delta_b.y) / determinant;
+                    let bitangent = (edge_c * delta_b.x - edge_b * delta_c.x) / determinant;
+                    (uv, tangent.normalized(), bitangent.normalized())
+                } else {
+                    (uv, edge_b.normalized(), edge_c.normalized())
+                }
+            }
+            None => (
+                weights,
+                (triangle.b - triangle.a).normalized(),
+                (triangle.c - triangle.a).normalized(),
+            ),
+        };
+
+        Hit {
+            ray_direction: ray.direction,
+            intersection: ray.at(distance),
+            normal,
+            geometric_normal,
+            uv,
+            tangent,
+            bitangent,
+        }
+    }
+}
+
+impl Shape for TriangleMesh {
+    type V = Vec3;
+
+    fn first_intersection(
+        &self,
+        ray: &Ray<Vec3>,
+        near_clipping: Component,
+    ) -> Option<Intersection<Hit<Vec3>>> {
+        self.bounds?
+            .intersection_range(ray, near_clipping, Component::INFINITY)?;
+        self.hit(ray, near_clipping)
+            .map(|(distance, index, weights)| Intersection {
+                distance,
+                data: self.surface_hit(ray, distance, index, weights),
+            })
+    }
+
+    fn occluded(&self, ray: &Ray<Vec3>, near_clipping: Component, far_clipping: Component) -> bool {
+        self.bounds
+            .and_then(|b| b.intersection_range(ray, near_clipping, far_clipping))
+            .is_some()
+            && self.triangles().any(|t| {
+                count_intersection_tests(1);
+                t.occluded(ray, near_clipping, far_clipping)
+            })
+    }
+
+    fn bounds(&self) -> Option<BoundingBox<Vec3>> {
+        self.bounds
+    }
+}
+
+impl SampleableShape for TriangleMesh {
+    fn area(&self) -> Component {
+        self.cumulative_areas.last().copied().unwrap_or(0.0)
+    }
+
+    /// picks a triangle in proportion to its area, then a point on it
+    fn sample_area(&self) -> ShapeSample<Vec3> {
+        let target = thread_rng().gen::<Component>() * self.area();
+        let index = self
+            .cumulative_areas
+            .partition_point(|&total| total <= target)
+            .min(self.triangle_count() - 1);
+        let sample = self.triangle(index).sample_area();
+        ShapeSample {
+            pdf: 1.0 / self.area(),
+            ..sample
+        }
+    }
+}
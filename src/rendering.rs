@@ -2,7 +2,7 @@ use super::*;
 use rayon::prelude::*;
 use std::sync::atomic::*;
 
-pub fn render_image<V: Vector, C: Camera<V = V>, E: SceneElement<V = V>>(
+pub fn render_image<V: Vector, C: Camera<V = V>, E: Scene<V = V>>(
     raytracer: &Raytracer<V, C, E>,
     width: usize,
     height: usize,
@@ -33,7 +33,7 @@ pub fn render_image<V: Vector, C: Camera<V = V>, E: SceneElement<V = V>>(
                     pixel_size,
                 );
                 let sum = (0..samples)
-                    .map(|_| raytracer.trace(&area, &options))
+                    .map(|_| raytracer.trace(&area, options))
                     .reduce(|c1, c2| c1 + c2)
                     .unwrap();
                 *pixel = (sum / samples_f).clamped();
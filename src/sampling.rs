@@ -0,0 +1,199 @@
+use super::*;
+use rand::*;
+use std::f32::consts;
+
+/// A direction around the z axis, more likely the closer it is to it; the pdf is `z / π`.
+pub fn cosine_weighted_hemisphere() -> Vec3 {
+    let mut rng = thread_rng();
+    let radius = rng.gen::<Component>().sqrt();
+    let angle = consts::TAU * rng.gen::<Component>();
+    let z = (1.0 - radius * radius).max(0.0).sqrt();
+    Vec3::new(radius * angle.cos(), radius * angle.sin(), z)
+}
+
+/// A direction around the z axis, each equally likely; the pdf is `1 / 2π`.
+pub fn uniform_hemisphere() -> Vec3 {
+    let mut rng = thread_rng();
+    let z = rng.gen::<Component>();
+    let radius = (1.0 - z * z).max(0.0).sqrt();
+    let angle = consts::TAU * rng.gen::<Component>();
+    Vec3::new(radius * angle.cos(), radius * angle.sin(), z)
+}
+
+/// A direction on the whole unit sphere, each equally likely; the pdf is `1 / 4π`.
+pub fn uniform_sphere() -> Vec3 {
+    let mut rng = thread_rng();
+    let z = 1.0 - 2.0 * rng.gen::<Component>();
+    let radius = (1.0 - z * z).max(0.0).sqrt();
+    let angle = consts::TAU * rng.gen::<Component>();
+    Vec3::new(radius * angle.cos(), radius * angle.sin(), z)
+}
+
+/// A direction within the cone around the z axis whose angle has the given cosine, each equally likely;
+/// the pdf is `1 / 2π(1 - cos_max)`.
+pub fn uniform_cone(cos_max: Component) -> Vec3 {
+    let mut rng = thread_rng();
+    let z = 1.0 - rng.gen::<Component>() * (1.0 - cos_max);
+    let radius = (1.0 - z * z).max(0.0).sqrt();
+    let angle = consts::TAU * rng.gen::<Component>();
+    Vec3::new(radius * angle.cos(), radius * angle.sin(), z)
+}
+
+/// Barycentric coordinates of a point on a triangle, each point equally likely.
+pub fn uniform_triangle() -> (Component, Component) {
+    let mut rng = thread_rng();
+    let root = rng.gen::<Component>().sqrt();
+    (1.0 - root, rng.gen::<Component>() * root)
+}
+
+/// Picks indices in proportion to a list of non-negative weights, using their cumulative distribution.
+#[derive(Debug, Clone)]
+pub struct Distribution1D {
+    /// running totals of the weights
+    cumulative: Vec<Component>,
+}
+
+impl Distribution1D {
+    /// panics if there are no weights
+    pub fn new<I: IntoIterator<Item = Component>>(weights: I) -> Self {
+        let mut total = 0.0;
+        let cumulative: Vec<_> = weights
+            .into_iter()
+            .map(|weight| {
+                total += weight.max(0.0);
+                total
+            })
+            .collect();
+        assert!(!cumulative.is_empty(), "cannot sample from no weights");
+        Self { cumulative }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cumulative.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cumulative.is_empty()
+    }
+
+    pub fn total(&self) -> Component {
+        *self.cumulative.last().unwrap()
+    }
+
+    pub fn weight(&self, index: usize) -> Component {
+        let previous = if index == 0 {
+            0.0
+        } else {
+            self.cumulative[index - 1]
+        };
+        self.cumulative[index] - previous
+    }
+
+    /// how likely `sample` is to pick the index; uniform if all weights are 0
+    pub fn probability(&self, index: usize) -> Component {
+        let total = self.total();
+        if total > 0.0 {
+            self.weight(index) / total
+        } else {
+            1.0 / self.len() as Component
+        }
+    }
+
+    /// an index along with the probability of picking it
+    pub fn sample(&self) -> (usize, Component) {
+        let mut rng = thread_rng();
+        let total = self.total();
+        let index = if total > 0.0 {
+            let target = rng.gen::<Component>() * total;
+            self.cumulative
+                .partition_point(|&sum| sum <= target)
+                .min(self.len() - 1)
+        } else {
+            rng.gen_range(0, self.len())
+        };
+        (index, self.probability(index))
+    }
+}
+
+/// Picks cells of a grid in proportion to their weights, first choosing a row and then a column within it.
+#[derive(Debug, Clone)]
+pub struct Distribution2D {
+    rows: Vec<Distribution1D>,
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    /// `weights` lists the cells row by row; panics unless it fills a non-empty grid of the given width
+    pub fn new(weights: &[Component], width: usize) -> Self {
+        assert!(width > 0 && !weights.is_empty() && weights.len().is_multiple_of(width));
+        let rows: Vec<_> = weights
+            .chunks(width)
+            .map(|row| Distribution1D::new(row.iter().copied()))
+            .collect();
+        let marginal = Distribution1D::new(rows.iter().map(|row| row.total()));
+        Self { rows, marginal }
+    }
+
+    /// how likely `sample` is to pick the cell
+    pub fn probability(&self, x: usize, y: usize) -> Component {
+        self.marginal.probability(y) * self.rows[y].probability(x)
+    }
+
+    /// the column and row of a cell, along with the probability of picking it
+    pub fn sample(&self) -> (usize, usize, Component) {
+        let (y, row_probability) = self.marginal.sample();
+        let (x, column_probability) = self.rows[y].sample();
+        (x, y, row_probability * column_probability)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLES: usize = 100_000;
+
+    #[test]
+    fn distribution_1d_samples_match_their_probabilities() {
+        let distribution = Distribution1D::new(vec![1.0, 0.0, 3.0, 4.0]);
+        let mut counts = [0; 4];
+        for _ in 0..SAMPLES {
+            let (index, probability) = distribution.sample();
+            assert_eq!(probability, distribution.probability(index));
+            counts[index] += 1;
+        }
+        for (index, &count) in counts.iter().enumerate() {
+            let frequency = count as Component / SAMPLES as Component;
+            assert!((frequency - distribution.probability(index)).abs() < 0.01);
+        }
+        assert_eq!(counts[1], 0);
+        assert_eq!(distribution.probability(2), 3.0 / 8.0);
+    }
+
+    #[test]
+    fn distribution_1d_without_weight_is_uniform() {
+        let distribution = Distribution1D::new(vec![0.0, -1.0, 0.0]);
+        for index in 0..3 {
+            assert_eq!(distribution.probability(index), 1.0 / 3.0);
+        }
+    }
+
+    #[test]
+    fn distribution_2d_samples_match_their_probabilities() {
+        let weights = [1.0, 2.0, 0.0, 0.0, 0.0, 0.0, 3.0, 0.5, 2.5];
+        let distribution = Distribution2D::new(&weights, 3);
+        let mut counts = [0; 9];
+        for _ in 0..SAMPLES {
+            let (x, y, probability) = distribution.sample();
+            assert!((probability - distribution.probability(x, y)).abs() < 1e-6);
+            counts[y * 3 + x] += 1;
+        }
+        let total: Component = weights.iter().sum();
+        for (cell, &count) in counts.iter().enumerate() {
+            let probability = distribution.probability(cell % 3, cell / 3);
+            assert!((probability - weights[cell] / total).abs() < 1e-6);
+            let frequency = count as Component / SAMPLES as Component;
+            assert!((frequency - probability).abs() < 0.01);
+        }
+    }
+}
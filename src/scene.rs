@@ -1,10 +1,113 @@
 use super::*;
+use rand::*;
+use std::cell::Cell;
+use std::sync::{Arc, OnceLock};
+
+/// the minimum distance to advance past a cut out hit before looking for the next one
+const CUTOUT_STEP: Component = 1e-4;
+
+thread_local! {
+    static INTERSECTION_TESTS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// How many shapes and bounding boxes rays have been tested against on this thread so far, for seeing what rays cost.
+pub fn intersection_tests() -> usize {
+    INTERSECTION_TESTS.with(|tests| tests.get())
+}
+
+/// adds to `intersection_tests`
+pub fn count_intersection_tests(count: usize) {
+    INTERSECTION_TESTS.with(|tests| tests.set(tests.get() + count));
+}
 
 pub trait Scene: SceneElement + Send + Sync {
-    fn add<E: SceneElement<V = Self::V>>(&mut self, element: E);
+    fn add<E: SceneElement<V = Self::V>>(&mut self, element: E) -> ElementId;
+
+    fn add_light<L: Light<V = Self::V>>(&mut self, light: L) -> LightId;
+
+    /// adds something that rays can hit and that is sampled as a light at the same time
+    fn add_area_light<L: Light<V = Self::V> + SceneElement<V = Self::V>>(
+        &mut self,
+        light: L,
+    ) -> LightId {
+        let light = Arc::new(light);
+        let id = self.add_light(light.clone());
+        self.add(LightElement { id, light });
+        id
+    }
+
+    fn lights(&self) -> &[Box<dyn Light<V = Self::V>>];
+
+    /// for picking one of `lights` at a time
+    fn light_sampler(&self) -> &LightSampler<Self::V>;
 }
 
-type IntersectionResult<V> = Option<Intersection<Behavior<V>>>;
+/// The closest hit on an element, from which the material's behavior can be evaluated once it's known to matter.
+pub struct SurfaceHit<'a, V: Vector> {
+    pub hit: Hit<V>,
+    pub material: &'a dyn Material<V>,
+    /// the light the surface belongs to, if it's also sampled as one
+    pub light: Option<(LightId, &'a dyn Light<V = V>)>,
+    pub visibility: Visibility,
+    /// the lights that illuminate the surface; `None` for all of them
+    pub light_links: Option<&'a LightLinks>,
+    /// the element with the shape that was hit, as nested in the scene
+    pub element: ElementId,
+    /// the material that was hit, shared by all instances of the element holding it
+    pub material_id: MaterialId,
+}
+
+impl<'a, V: Vector> SurfaceHit<'a, V> {
+    pub fn behavior(self) -> Behavior<V> {
+        self.material.behavior(self.hit)
+    }
+}
+
+pub type ElementIntersection<'a, V> = Option<Intersection<SurfaceHit<'a, V>>>;
+
+/// Identifies an element of a scene, handed out as elements are added.
+///
+/// Hits on elements nested in others, e.g. in a `BvhScene` that's instanced several times,
+/// combine the ids at each level, so they're told apart too.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct ElementId(pub u32);
+
+impl ElementId {
+    /// The id of a hit on the element `inner`, which is nested in this one.
+    ///
+    /// The default id is that of no element in particular, e.g. of hits on shapes before a scene tells them apart,
+    /// and leaves the other id as it is.
+    pub fn nest(self, inner: ElementId) -> ElementId {
+        if self == ElementId::default() {
+            inner
+        } else if inner == ElementId::default() {
+            self
+        } else {
+            // scramble the outer id, so it doesn't cancel out with a similar inner one
+            ElementId(self.0.wrapping_mul(0x9e37_79b9).rotate_left(16) ^ inner.0)
+        }
+    }
+}
+
+/// Identifies a material of a scene, handed out along with the id of the element it was added with.
+///
+/// Hits take the id of the innermost element that was given one, which is the one holding the material,
+/// so instances of an element share the ids of its materials.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct MaterialId(pub u32);
+
+/// the intersection with the id of the element it's nested in
+pub(crate) fn nested<V: Vector>(
+    intersection: Intersection<SurfaceHit<'_, V>>,
+    id: ElementId,
+) -> Intersection<SurfaceHit<'_, V>> {
+    let Intersection { distance, mut data } = intersection;
+    data.element = id.nest(data.element);
+    if data.material_id == MaterialId::default() {
+        data.material_id = MaterialId(id.0);
+    }
+    Intersection { distance, data }
+}
 
 pub trait SceneElement: 'static + Send + Sync {
     type V: Vector;
@@ -13,7 +116,48 @@ pub trait SceneElement: 'static + Send + Sync {
         &self,
         ray: Ray<Self::V>,
         near_clipping: Component,
-    ) -> IntersectionResult<Self::V>;
+    ) -> ElementIntersection<'_, Self::V>;
+
+    /// whether anything is hit between `near_clipping` and `far_clipping`, e.g. to check if a light is visible
+    fn occluded(
+        &self,
+        ray: Ray<Self::V>,
+        near_clipping: Component,
+        far_clipping: Component,
+    ) -> bool {
+        self.first_intersection(ray, near_clipping)
+            .is_some_and(|i| i.distance < far_clipping)
+    }
+
+    /// `None` for elements that extend infinitely
+    fn bounds(&self) -> Option<BoundingBox<Self::V>> {
+        None
+    }
+}
+
+impl<E: SceneElement + ?Sized> SceneElement for Arc<E> {
+    type V = E::V;
+
+    fn first_intersection(
+        &self,
+        ray: Ray<Self::V>,
+        near_clipping: Component,
+    ) -> ElementIntersection<'_, Self::V> {
+        (**self).first_intersection(ray, near_clipping)
+    }
+
+    fn occluded(
+        &self,
+        ray: Ray<Self::V>,
+        near_clipping: Component,
+        far_clipping: Component,
+    ) -> bool {
+        (**self).occluded(ray, near_clipping, far_clipping)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox<Self::V>> {
+        (**self).bounds()
+    }
 }
 
 pub struct MaterialShape<V: Vector, S: Shape<V = V>, M: Material<V>> {
@@ -21,6 +165,27 @@ pub struct MaterialShape<V: Vector, S: Shape<V = V>, M: Material<V>> {
     pub material: M,
 }
 
+impl<V: Vector, S: Shape<V = V>, M: Material<V>> MaterialShape<V, S, M> {
+    /// the closest hit that isn't rejected by the material's cutouts, continuing past those that are
+    fn first_opaque_intersection(
+        &self,
+        ray: &Ray<V>,
+        near_clipping: Component,
+    ) -> Option<Intersection<Hit<V>>> {
+        let step = near_clipping.max(CUTOUT_STEP);
+        let mut near_clipping = near_clipping;
+        loop {
+            count_intersection_tests(1);
+            let intersection = self.shape.first_intersection(ray, near_clipping)?;
+            let opacity = self.material.opacity(&intersection.data);
+            if opacity >= 1.0 || (opacity > 0.0 && thread_rng().gen::<Component>() < opacity) {
+                return Some(intersection);
+            }
+            near_clipping = intersection.distance + step;
+        }
+    }
+}
+
 impl<V: Vector, S: Shape<V = V>, M: Material<V>> SceneElement for MaterialShape<V, S, M> {
     type V = V;
 
@@ -28,29 +193,143 @@ impl<V: Vector, S: Shape<V = V>, M: Material<V>> SceneElement for MaterialShape<
         &self,
         ray: Ray<Self::V>,
         near_clipping: Component,
-    ) -> IntersectionResult<Self::V> {
-        self.shape
-            .first_intersection(&ray, near_clipping)
-            .map(|i| Intersection {
-                distance: i.distance,
-                data: self.material.behavior(i.data),
+    ) -> ElementIntersection<'_, Self::V> {
+        let intersection = if self.material.has_cutouts() {
+            self.first_opaque_intersection(&ray, near_clipping)
+        } else {
+            count_intersection_tests(1);
+            self.shape.first_intersection(&ray, near_clipping)
+        };
+        intersection.map(|i| Intersection {
+            distance: i.distance,
+            data: SurfaceHit {
+                hit: i.data,
+                material: &self.material,
+                light: None,
+                visibility: Visibility::default(),
+                light_links: None,
+                element: ElementId::default(),
+                material_id: MaterialId::default(),
+            },
+        })
+    }
+
+    fn occluded(
+        &self,
+        ray: Ray<Self::V>,
+        near_clipping: Component,
+        far_clipping: Component,
+    ) -> bool {
+        if self.material.has_cutouts() {
+            self.first_opaque_intersection(&ray, near_clipping)
+                .is_some_and(|i| i.distance < far_clipping)
+        } else {
+            count_intersection_tests(1);
+            self.shape.occluded(&ray, near_clipping, far_clipping)
+        }
+    }
+
+    fn bounds(&self) -> Option<BoundingBox<Self::V>> {
+        self.shape.bounds()
+    }
+}
+
+/// An element that's sampled as a light at the same time, telling hits on it which light they found.
+struct LightElement<L> {
+    id: LightId,
+    light: Arc<L>,
+}
+
+impl<V: Vector, L: Light<V = V> + SceneElement<V = V>> SceneElement for LightElement<L> {
+    type V = V;
+
+    fn first_intersection(
+        &self,
+        ray: Ray<Self::V>,
+        near_clipping: Component,
+    ) -> ElementIntersection<'_, Self::V> {
+        self.light
+            .first_intersection(ray, near_clipping)
+            .map(|mut i| {
+                i.data.light = Some((self.id, &*self.light));
+                i
             })
     }
+
+    fn occluded(
+        &self,
+        ray: Ray<Self::V>,
+        near_clipping: Component,
+        far_clipping: Component,
+    ) -> bool {
+        self.light.occluded(ray, near_clipping, far_clipping)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox<Self::V>> {
+        SceneElement::bounds(&*self.light)
+    }
 }
 
 pub struct VecScene<V: Vector> {
-    elements: Vec<Box<dyn SceneElement<V = V>>>,
+    elements: Vec<(ElementId, Box<dyn SceneElement<V = V>>)>,
+    /// how many elements have been added, to hand out ids even after `into_bvh`
+    element_count: u32,
+    lights: Vec<Box<dyn Light<V = V>>>,
+    /// built on first use, since the lights can't change while rendering
+    light_sampler: OnceLock<LightSampler<V>>,
 }
 
 impl<V: Vector> VecScene<V> {
     pub fn new() -> Self {
-        Self { elements: vec![] }
+        Self {
+            elements: vec![],
+            element_count: 0,
+            lights: vec![],
+            light_sampler: OnceLock::new(),
+        }
+    }
+
+    /// Builds an acceleration structure over the elements added so far, as the only element of a new scene that keeps the lights.
+    pub fn into_bvh(self) -> VecScene<V> {
+        // the hierarchy keeps the ids the elements were added with
+        let bvh = BvhScene::with_ids(self.elements);
+        Self {
+            elements: vec![(ElementId::default(), Box::new(bvh))],
+            element_count: self.element_count,
+            lights: self.lights,
+            light_sampler: self.light_sampler,
+        }
+    }
+}
+
+impl<V: Vector> Default for VecScene<V> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl<V: Vector> Scene for VecScene<V> {
-    fn add<E: SceneElement<V = Self::V>>(&mut self, element: E) {
-        self.elements.push(Box::new(element));
+    fn add<E: SceneElement<V = Self::V>>(&mut self, element: E) -> ElementId {
+        self.element_count += 1;
+        let id = ElementId(self.element_count);
+        self.elements.push((id, Box::new(element)));
+        id
+    }
+
+    fn add_light<L: Light<V = Self::V>>(&mut self, light: L) -> LightId {
+        let id = LightId(self.lights.len());
+        self.lights.push(Box::new(light));
+        self.light_sampler = OnceLock::new();
+        id
+    }
+
+    fn lights(&self) -> &[Box<dyn Light<V = Self::V>>] {
+        &self.lights
+    }
+
+    fn light_sampler(&self) -> &LightSampler<Self::V> {
+        self.light_sampler
+            .get_or_init(|| LightSampler::new(&self.lights))
     }
 }
 
@@ -61,10 +340,29 @@ impl<V: Vector> SceneElement for VecScene<V> {
         &self,
         ray: Ray<Self::V>,
         near_clipping: Component,
-    ) -> IntersectionResult<Self::V> {
+    ) -> ElementIntersection<'_, Self::V> {
         self.elements
             .iter()
-            .filter_map(|e| e.first_intersection(ray, near_clipping))
+            .filter_map(|(id, e)| Some(nested(e.first_intersection(ray, near_clipping)?, *id)))
             .min_by(|l, r| l.distance.partial_cmp(&r.distance).unwrap())
     }
+
+    fn occluded(
+        &self,
+        ray: Ray<Self::V>,
+        near_clipping: Component,
+        far_clipping: Component,
+    ) -> bool {
+        self.elements
+            .iter()
+            .any(|(_, e)| e.occluded(ray, near_clipping, far_clipping))
+    }
+
+    fn bounds(&self) -> Option<BoundingBox<Self::V>> {
+        self.elements
+            .iter()
+            .map(|(_, e)| e.bounds())
+            .reduce(|l, r| l.and_then(|l| r.map(|r| l.union(&r))))
+            .flatten()
+    }
 }
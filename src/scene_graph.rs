@@ -0,0 +1,198 @@
+use super::*;
+use std::sync::Arc;
+
+pub enum NodeContent {
+    Group(Vec<SceneNode>),
+    Object {
+        shape: Arc<dyn Shape<V = Vec3>>,
+        material: Arc<dyn Material<Vec3>>,
+    },
+    /// a prebuilt element, whose materials can't be overridden
+    Element(Arc<dyn SceneElement<V = Vec3>>),
+}
+
+pub struct SceneNode {
+    pub name: String,
+    /// relative to the parent node
+    pub transform: Transform,
+    /// replaces the material of every object in this subtree, unless a node further down overrides it again
+    pub material_override: Option<Arc<dyn Material<Vec3>>>,
+    /// hidden nodes are left out of the built scene along with all their descendants
+    pub is_visible: bool,
+    pub content: NodeContent,
+}
+
+impl SceneNode {
+    fn new(name: &str, content: NodeContent) -> Self {
+        Self {
+            name: name.to_owned(),
+            transform: Transform::identity(),
+            material_override: None,
+            is_visible: true,
+            content,
+        }
+    }
+
+    pub fn group(name: &str, children: Vec<SceneNode>) -> Self {
+        Self::new(name, NodeContent::Group(children))
+    }
+
+    pub fn object(
+        name: &str,
+        shape: Arc<dyn Shape<V = Vec3>>,
+        material: Arc<dyn Material<Vec3>>,
+    ) -> Self {
+        Self::new(name, NodeContent::Object { shape, material })
+    }
+
+    pub fn element(name: &str, element: Arc<dyn SceneElement<V = Vec3>>) -> Self {
+        Self::new(name, NodeContent::Element(element))
+    }
+
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    pub fn with_material_override(mut self, material: Arc<dyn Material<Vec3>>) -> Self {
+        self.material_override = Some(material);
+        self
+    }
+
+    pub fn with_visibility(mut self, is_visible: bool) -> Self {
+        self.is_visible = is_visible;
+        self
+    }
+
+    pub fn children(&self) -> &[SceneNode] {
+        match &self.content {
+            NodeContent::Group(children) => children,
+            _ => &[],
+        }
+    }
+
+    /// panics if this is not a group node
+    pub fn add_child(&mut self, child: SceneNode) {
+        match &mut self.content {
+            NodeContent::Group(children) => children.push(child),
+            _ => panic!("cannot add children to non-group node {}", self.name),
+        }
+    }
+
+    /// depth-first search through this node and its descendants
+    pub fn find(&self, name: &str) -> Option<&SceneNode> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.children().iter().find_map(|c| c.find(name))
+    }
+
+    pub fn find_mut(&mut self, name: &str) -> Option<&mut SceneNode> {
+        if self.name == name {
+            return Some(self);
+        }
+        match &mut self.content {
+            NodeContent::Group(children) => children.iter_mut().find_map(|c| c.find_mut(name)),
+            _ => None,
+        }
+    }
+
+    /// removes the first descendant with the given name, returning it
+    pub fn remove(&mut self, name: &str) -> Option<SceneNode> {
+        match &mut self.content {
+            NodeContent::Group(children) => {
+                if let Some(index) = children.iter().position(|c| c.name == name) {
+                    Some(children.remove(index))
+                } else {
+                    children.iter_mut().find_map(|c| c.remove(name))
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// swaps the first node with the given name for `node`, returning the old one
+    pub fn replace(&mut self, name: &str, node: SceneNode) -> Option<SceneNode> {
+        self.find_mut(name)
+            .map(|existing| std::mem::replace(existing, node))
+    }
+
+    fn flatten(
+        &self,
+        parent_transform: &Transform,
+        parent_material: Option<&Arc<dyn Material<Vec3>>>,
+        elements: &mut Vec<Box<dyn SceneElement<V = Vec3>>>,
+    ) {
+        if !self.is_visible {
+            return;
+        }
+
+        let transform = self.transform.then(parent_transform);
+        let override_material = self.material_override.as_ref().or(parent_material);
+
+        match &self.content {
+            NodeContent::Group(children) => {
+                for child in children {
+                    child.flatten(&transform, override_material, elements);
+                }
+            }
+            NodeContent::Object { shape, material } => {
+                elements.push(Box::new(MaterialShape {
+                    shape: Transformed::new(shape.clone(), transform),
+                    material: override_material.unwrap_or(material).clone(),
+                }));
+            }
+            NodeContent::Element(element) => {
+                elements.push(Box::new(Transformed::new(element.clone(), transform)));
+            }
+        }
+    }
+}
+
+/// A hierarchy of named nodes, flattened into an acceleration structure for rendering.
+pub struct SceneGraph {
+    pub root: SceneNode,
+}
+
+impl SceneGraph {
+    pub fn new() -> Self {
+        Self {
+            root: SceneNode::group("root", vec![]),
+        }
+    }
+
+    pub fn add(&mut self, node: SceneNode) {
+        self.root.add_child(node);
+    }
+
+    pub fn find(&self, name: &str) -> Option<&SceneNode> {
+        self.root.find(name)
+    }
+
+    pub fn find_mut(&mut self, name: &str) -> Option<&mut SceneNode> {
+        self.root.find_mut(name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<SceneNode> {
+        self.root.remove(name)
+    }
+
+    pub fn replace(&mut self, name: &str, node: SceneNode) -> Option<SceneNode> {
+        self.root.replace(name, node)
+    }
+
+    /// Flattens the visible nodes into an acceleration structure,
+    /// numbering each object and instance for its `ElementId` so instances of the same element are told apart.
+    pub fn build(&self) -> BvhScene<Vec3> {
+        let mut elements = vec![];
+        self.root
+            .flatten(&Transform::identity(), None, &mut elements);
+        BvhScene::new(elements)
+    }
+}
+
+impl Default for SceneGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
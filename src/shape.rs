@@ -1,14 +1,62 @@
 use super::*;
+use rand::*;
+use std::f32::consts;
+use std::sync::Arc;
 
 pub struct Intersection<Data> {
     pub distance: Component,
     pub data: Data,
 }
 
+#[derive(Copy, Clone)]
 pub struct Hit<V: Vector> {
     pub ray_direction: Normalized<V>,
     pub intersection: V,
+    /// the normal to shade with, which may differ from the geometric one (e.g. for normal mapping)
     pub normal: Normalized<V>,
+    /// the actual orientation of the surface
+    pub geometric_normal: Normalized<V>,
+    /// texture coordinates, usually in `0.0..=1.0`
+    pub uv: Vec2,
+    /// the direction along the surface in which `u` increases
+    pub tangent: Normalized<V>,
+    /// the direction along the surface in which `v` increases
+    pub bitangent: Normalized<V>,
+}
+
+/// how far rays leaving a surface are nudged off it, relative to their distance from the origin
+const RAY_OFFSET: Component = 1e-4;
+
+impl<V: Vector> Hit<V> {
+    /// whether the ray hit the back of the surface, judging by the geometric normal
+    pub fn is_back_face(&self) -> bool {
+        self.geometric_normal.dot(self.ray_direction) > 0.0
+    }
+
+    /// the shading normal, flipped to face the side the ray came from
+    pub fn facing_normal(&self) -> Normalized<V> {
+        if self.is_back_face() {
+            -self.normal
+        } else {
+            self.normal
+        }
+    }
+
+    /// A ray leaving the surface in the given direction.
+    ///
+    /// Its origin is nudged off the surface along the geometric normal, so it doesn't hit the surface again right away.
+    pub fn next_ray(&self, direction: V) -> Ray<V> {
+        let offset = RAY_OFFSET * self.intersection.norm().max(1.0);
+        let offset = if direction.dot(self.geometric_normal) < 0.0 {
+            -offset
+        } else {
+            offset
+        };
+        Ray::new(
+            self.intersection + self.geometric_normal * offset,
+            direction,
+        )
+    }
 }
 
 type IntersectionResult<V> = Option<Intersection<Hit<V>>>;
@@ -21,6 +69,110 @@ pub trait Shape: 'static + Send + Sync {
         ray: &Ray<Self::V>,
         near_clipping: Component,
     ) -> IntersectionResult<Self::V>;
+
+    /// whether anything is hit between `near_clipping` and `far_clipping`, which can be cheaper than finding the closest hit
+    fn occluded(
+        &self,
+        ray: &Ray<Self::V>,
+        near_clipping: Component,
+        far_clipping: Component,
+    ) -> bool {
+        self.first_intersection(ray, near_clipping)
+            .is_some_and(|i| i.distance < far_clipping)
+    }
+
+    /// `None` for shapes that extend infinitely
+    fn bounds(&self) -> Option<BoundingBox<Self::V>> {
+        None
+    }
+}
+
+/// A point picked on a shape's surface.
+pub struct ShapeSample<V: Vector> {
+    pub position: V,
+    pub normal: Normalized<V>,
+    /// the probability density of picking this point, per unit area or solid angle depending on how it was sampled
+    pub pdf: Component,
+}
+
+/// Shapes that points can be picked on, e.g. to sample light coming from them.
+pub trait SampleableShape: Shape {
+    fn area(&self) -> Component;
+
+    /// a point picked uniformly over the whole surface, with its pdf per unit area; only for shapes with a positive area
+    fn sample_area(&self) -> ShapeSample<Self::V>;
+
+    /// A point picked on the surface as seen from `reference`, with its pdf per unit solid angle.
+    ///
+    /// By default this converts a sample picked uniformly over the surface;
+    /// shapes can focus on the part visible from `reference` instead.
+    fn sample_from(&self, reference: Self::V) -> Option<ShapeSample<Self::V>> {
+        let sample = self.sample_area();
+        let offset = sample.position - reference;
+        let pdf = area_to_solid_angle(sample.pdf, offset, sample.normal)?;
+        Some(ShapeSample { pdf, ..sample })
+    }
+
+    /// the pdf per unit solid angle of `sample_from` picking the first point hit along `direction`, 0 if there is none
+    fn pdf_from(&self, reference: Self::V, direction: Normalized<Self::V>) -> Component {
+        let ray = Ray {
+            origin: reference,
+            direction,
+        };
+        self.first_intersection(&ray, 0.0)
+            .and_then(|i| {
+                let offset = i.data.intersection - reference;
+                area_to_solid_angle(1.0 / self.area(), offset, i.data.geometric_normal)
+            })
+            .unwrap_or(0.0)
+    }
+
+    /// the normal everywhere on the surface if it's flat, which lets lights on it be skipped from behind
+    fn flat_normal(&self) -> Option<Normalized<Self::V>> {
+        None
+    }
+}
+
+/// converts a pdf per unit area at a point at the given offset to one per unit solid angle, `None` if it's seen edge-on
+fn area_to_solid_angle<V: Vector>(
+    pdf: Component,
+    offset: V,
+    normal: Normalized<V>,
+) -> Option<Component> {
+    let distance_sq = offset.squared_sum();
+    if distance_sq <= 0.0 {
+        return None;
+    }
+    let cos = normal.dot(offset).abs() / distance_sq.sqrt();
+    if cos <= 1e-6 {
+        return None;
+    }
+    Some(pdf * distance_sq / cos)
+}
+
+impl<S: Shape + ?Sized> Shape for Arc<S> {
+    type V = S::V;
+
+    fn first_intersection(
+        &self,
+        ray: &Ray<Self::V>,
+        near_clipping: Component,
+    ) -> IntersectionResult<Self::V> {
+        (**self).first_intersection(ray, near_clipping)
+    }
+
+    fn occluded(
+        &self,
+        ray: &Ray<Self::V>,
+        near_clipping: Component,
+        far_clipping: Component,
+    ) -> bool {
+        (**self).occluded(ray, near_clipping, far_clipping)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox<Self::V>> {
+        (**self).bounds()
+    }
 }
 
 pub type Circle = NSphere<Vec2>;
@@ -32,15 +184,9 @@ pub struct NSphere<V: Vector> {
     pub radius: Component,
 }
 
-impl<V: Vector> Shape for NSphere<V> {
-    type V = V;
-
-    fn first_intersection(&self, ray: &Ray<V>, near_clipping: Component) -> IntersectionResult<V> {
+impl<V: Vector> NSphere<V> {
+    fn distance(&self, ray: &Ray<V>, near_clipping: Component) -> Option<Component> {
         let offset_center = self.center - ray.origin;
-        // check that sphere is in front of the ray
-        if offset_center.dot(*ray.direction) <= 0.0 {
-            return None;
-        }
         // project sphere center onto ray
         let projection_length = offset_center.dot(*ray.direction);
         let projection = *ray.direction * projection_length;
@@ -48,23 +194,285 @@ impl<V: Vector> Shape for NSphere<V> {
         let hypotenuse_sq = self.radius.squared();
         let cathetus_sq = (offset_center - projection).squared_sum();
         if hypotenuse_sq < cathetus_sq {
-            return None; // TODO: when does this occur?
+            return None; // ray passes by the sphere
         }
-        let distance = projection_length - (hypotenuse_sq - cathetus_sq).sqrt();
-        if distance < near_clipping {
-            None
-        } else {
+        let half_chord = (hypotenuse_sq - cathetus_sq).sqrt();
+        // the far side is hit when starting inside the sphere
+        [
+            projection_length - half_chord,
+            projection_length + half_chord,
+        ]
+        .iter()
+        .copied()
+        .find(|&distance| distance >= near_clipping)
+    }
+}
+
+impl<V: SphericalMapping> Shape for NSphere<V> {
+    type V = V;
+
+    fn first_intersection(&self, ray: &Ray<V>, near_clipping: Component) -> IntersectionResult<V> {
+        self.distance(ray, near_clipping).map(|distance| {
             let intersection = ray.at(distance);
-            Some(Intersection {
+            let normal = (intersection - self.center).normalized();
+            let (uv, tangent, bitangent) = V::spherical_mapping(normal);
+            Intersection {
                 distance,
                 data: Hit {
                     ray_direction: ray.direction,
                     intersection,
-                    normal: (intersection - self.center).normalized(),
+                    normal,
+                    geometric_normal: normal,
+                    uv,
+                    tangent,
+                    bitangent,
                 },
-            })
+            }
+        })
+    }
+
+    fn occluded(&self, ray: &Ray<V>, near_clipping: Component, far_clipping: Component) -> bool {
+        self.distance(ray, near_clipping)
+            .is_some_and(|d| d < far_clipping)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox<V>> {
+        let radius = V::uniform(self.radius);
+        Some(BoundingBox::new(self.center - radius, self.center + radius))
+    }
+}
+
+impl SampleableShape for Sphere {
+    fn area(&self) -> Component {
+        2.0 * consts::TAU * self.radius.squared()
+    }
+
+    fn sample_area(&self) -> ShapeSample<Vec3> {
+        let normal = uniform_sphere().normalized();
+        ShapeSample {
+            position: self.center + normal * self.radius,
+            normal,
+            pdf: 1.0 / self.area(),
+        }
+    }
+
+    /// only picks points on the side facing `reference`, within the cone the sphere covers as seen from there
+    fn sample_from(&self, reference: Vec3) -> Option<ShapeSample<Vec3>> {
+        let offset = self.center - reference;
+        let distance_sq = offset.squared_sum();
+        if distance_sq <= self.radius.squared() {
+            // every point on the sphere is visible from inside
+            let sample = self.sample_area();
+            let pdf = area_to_solid_angle(sample.pdf, sample.position - reference, sample.normal)?;
+            return Some(ShapeSample { pdf, ..sample });
+        }
+
+        let cos_max = (1.0 - self.radius.squared() / distance_sq).max(0.0).sqrt();
+        let direction = Frame::new(offset.normalized(), *Vec3::positive_x())
+            .to_world(uniform_cone(cos_max))
+            .normalized();
+
+        // the closest point along the direction, which may only graze the sphere due to rounding
+        let projection = offset.dot(direction);
+        let half_chord_sq = (self.radius.squared() - (distance_sq - projection.squared())).max(0.0);
+        let position = reference + direction * (projection - half_chord_sq.sqrt());
+        Some(ShapeSample {
+            position,
+            normal: (position - self.center).normalized(),
+            pdf: self.cone_pdf(distance_sq),
+        })
+    }
+
+    fn pdf_from(&self, reference: Vec3, direction: Normalized<Vec3>) -> Component {
+        let offset = self.center - reference;
+        let distance_sq = offset.squared_sum();
+        let ray = Ray {
+            origin: reference,
+            direction,
+        };
+        let distance = match self.distance(&ray, 0.0) {
+            Some(distance) => distance,
+            None => return 0.0,
+        };
+        if distance_sq <= self.radius.squared() {
+            let position = ray.at(distance);
+            let normal = (position - self.center).normalized();
+            return area_to_solid_angle(1.0 / self.area(), position - reference, normal)
+                .unwrap_or(0.0);
+        }
+        self.cone_pdf(distance_sq)
+    }
+}
+
+impl Sphere {
+    /// the pdf per unit solid angle of sampling the cone covered by the sphere, seen from the given squared distance
+    fn cone_pdf(&self, distance_sq: Component) -> Component {
+        let sin_max_sq = self.radius.squared() / distance_sq;
+        let cos_max = (1.0 - sin_max_sq).max(0.0).sqrt();
+        // 1 - cos, without losing precision for small cones
+        let solid_angle = consts::TAU * sin_max_sq / (1.0 + cos_max);
+        1.0 / solid_angle
+    }
+}
+
+/// A flat parallelogram spanned by two edges from a corner, which are also the directions of its texture coordinates.
+///
+/// Its normal faces the side from which `edge_u` points right and `edge_v` points up.
+pub struct Quad {
+    pub corner: Vec3,
+    pub edge_u: Vec3,
+    pub edge_v: Vec3,
+}
+
+impl Quad {
+    pub fn normal(&self) -> Normalized<Vec3> {
+        self.edge_v.cross(self.edge_u).normalized()
+    }
+
+    pub fn area(&self) -> Component {
+        self.edge_u.cross(self.edge_v).norm()
+    }
+
+    /// distance and texture coordinates of the hit
+    fn hit(&self, ray: &Ray<Vec3>, near_clipping: Component) -> Option<(Component, Vec2)> {
+        let perpendicular = self.edge_u.cross(self.edge_v);
+        let denominator = perpendicular.dot(ray.direction);
+        if denominator.abs() < 1e-9 {
+            return None; // parallel to the plane
+        }
+        let distance = perpendicular.dot(self.corner - ray.origin) / denominator;
+        if distance < near_clipping {
+            return None;
+        }
+        // project the offset onto the edges to get texture coordinates
+        let offset = ray.at(distance) - self.corner;
+        let scale = perpendicular / perpendicular.squared_sum();
+        let u = scale.dot(offset.cross(self.edge_v));
+        let v = scale.dot(self.edge_u.cross(offset));
+        if (0.0..=1.0).contains(&u) && (0.0..=1.0).contains(&v) {
+            Some((distance, Vec2::new(u, v)))
+        } else {
+            None
+        }
+    }
+}
+
+impl Shape for Quad {
+    type V = Vec3;
+
+    fn first_intersection(
+        &self,
+        ray: &Ray<Vec3>,
+        near_clipping: Component,
+    ) -> IntersectionResult<Vec3> {
+        self.hit(ray, near_clipping).map(|(distance, uv)| {
+            let normal = self.normal();
+            Intersection {
+                distance,
+                data: Hit {
+                    ray_direction: ray.direction,
+                    intersection: ray.at(distance),
+                    normal,
+                    geometric_normal: normal,
+                    uv,
+                    tangent: self.edge_u.normalized(),
+                    bitangent: self.edge_v.normalized(),
+                },
+            }
+        })
+    }
+
+    fn occluded(&self, ray: &Ray<Vec3>, near_clipping: Component, far_clipping: Component) -> bool {
+        self.hit(ray, near_clipping)
+            .is_some_and(|(distance, _)| distance < far_clipping)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox<Vec3>> {
+        let corners = [
+            self.corner,
+            self.corner + self.edge_u,
+            self.corner + self.edge_v,
+            self.corner + self.edge_u + self.edge_v,
+        ];
+        // pad the box so it doesn't end up infinitely thin
+        let padding = Vec3::uniform(1e-4);
+        BoundingBox::around_points(corners.iter().copied())
+            .map(|b| BoundingBox::new(b.min - padding, b.max + padding))
+    }
+}
+
+impl SampleableShape for Quad {
+    fn area(&self) -> Component {
+        Quad::area(self)
+    }
+
+    fn sample_area(&self) -> ShapeSample<Vec3> {
+        let mut rng = thread_rng();
+        ShapeSample {
+            position: self.corner
+                + self.edge_u * rng.gen::<Component>()
+                + self.edge_v * rng.gen::<Component>(),
+            normal: self.normal(),
+            pdf: 1.0 / Quad::area(self),
         }
     }
+
+    fn flat_normal(&self) -> Option<Normalized<Vec3>> {
+        Some(self.normal())
+    }
+}
+
+/// Maps directions from the center of an n-sphere to texture coordinates,
+/// along with the tangents in the directions of increasing `u` and `v`.
+pub trait SphericalMapping: Vector {
+    fn spherical_mapping(direction: Normalized<Self>)
+        -> (Vec2, Normalized<Self>, Normalized<Self>);
+}
+
+impl SphericalMapping for Vec2 {
+    /// the outline of a circle only has one tangent direction, so the bitangent matches the tangent
+    fn spherical_mapping(
+        direction: Normalized<Self>,
+    ) -> (Vec2, Normalized<Self>, Normalized<Self>) {
+        let u = direction.y.atan2(direction.x) / consts::TAU + 0.5;
+        let tangent = Vec2::new(-direction.y, direction.x).normalized();
+        (Vec2::new(u, 0.5), tangent, tangent)
+    }
+}
+
+impl SphericalMapping for Vec3 {
+    /// `u` goes around the y axis and `v` from the bottom pole to the top one
+    fn spherical_mapping(
+        direction: Normalized<Self>,
+    ) -> (Vec2, Normalized<Self>, Normalized<Self>) {
+        let u = direction.z.atan2(direction.x) / consts::TAU + 0.5;
+        let v = 1.0 - direction.y.clamp(-1.0, 1.0).acos() / consts::PI;
+        let around = Vec3::new(-direction.z, 0.0, direction.x);
+        let tangent = if around.squared_sum() > 0.0 {
+            around.normalized()
+        } else {
+            Vec3::positive_z() // at the poles, any direction will do
+        };
+        let bitangent = tangent.cross(direction);
+        (Vec2::new(u, v), tangent, bitangent)
+    }
+}
+
+impl SphericalMapping for Vec4 {
+    /// maps the direction's projection into xyz space, ignoring w
+    fn spherical_mapping(
+        direction: Normalized<Self>,
+    ) -> (Vec2, Normalized<Self>, Normalized<Self>) {
+        let projected = Vec3::new(direction.x, direction.y, direction.z);
+        let projected = if projected.squared_sum() > 0.0 {
+            projected.normalized()
+        } else {
+            Vec3::positive_y()
+        };
+        let (uv, tangent, bitangent) = Vec3::spherical_mapping(projected);
+        let extend = |v: Normalized<Vec3>| Vec4::new(v.x, v.y, v.z, 0.0).normalized();
+        (uv, extend(tangent), extend(bitangent))
+    }
 }
 
 trait Squareable {
@@ -0,0 +1,21 @@
+use super::*;
+
+/// Alternates between two textures in a grid over the texture coordinates.
+pub struct CheckerboardTexture<A, B> {
+    pub even: A,
+    pub odd: B,
+    /// how many squares fit along `u` and `v` respectively
+    pub repetitions: Vec2,
+}
+
+impl<V: Vector, A: Texture<V>, B: Texture<V>> Texture<V> for CheckerboardTexture<A, B> {
+    fn color_at(&self, hit: &Hit<V>) -> Color {
+        let u = (hit.uv.x * self.repetitions.x).floor() as i64;
+        let v = (hit.uv.y * self.repetitions.y).floor() as i64;
+        if (u + v) % 2 == 0 {
+            self.even.color_at(hit)
+        } else {
+            self.odd.color_at(hit)
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use super::*;
+
+/// Maps values to colors by interpolating linearly between stops.
+#[derive(Clone)]
+pub struct ColorRamp {
+    stops: Vec<(Component, Color)>,
+}
+
+impl ColorRamp {
+    /// panics without any stops
+    pub fn new(mut stops: Vec<(Component, Color)>) -> Self {
+        assert!(!stops.is_empty());
+        stops.sort_by(|(l, _), (r, _)| l.partial_cmp(r).unwrap());
+        Self { stops }
+    }
+
+    pub fn between(low: (Component, Color), high: (Component, Color)) -> Self {
+        Self::new(vec![low, high])
+    }
+
+    /// values outside the stops take on the color of the nearest one
+    pub fn color_at(&self, value: Component) -> Color {
+        let next = self
+            .stops
+            .iter()
+            .position(|(position, _)| *position > value);
+        match next {
+            None => self.stops.last().unwrap().1,
+            Some(0) => self.stops[0].1,
+            Some(index) => {
+                let (start, low) = self.stops[index - 1];
+                let (end, high) = self.stops[index];
+                let factor = (value - start) / (end - start);
+                low * (1.0 - factor) + high * factor
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use super::*;
+use image_lib::ImageResult;
+use std::path::Path;
+
+/// What to do with texture coordinates outside of `0.0..=1.0`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum WrapMode {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+}
+
+impl WrapMode {
+    fn wrap(self, index: i64, size: usize) -> usize {
+        let size = size as i64;
+        let wrapped = match self {
+            WrapMode::Repeat => index.rem_euclid(size),
+            WrapMode::MirroredRepeat => {
+                let period = index.rem_euclid(2 * size);
+                if period < size {
+                    period
+                } else {
+                    2 * size - 1 - period
+                }
+            }
+            WrapMode::ClampToEdge => index.clamp(0, size - 1),
+        };
+        wrapped as usize
+    }
+}
+
+/// Looks up colors from an image using bilinear filtering, with `v = 0` at the bottom.
+pub struct ImageTexture {
+    pub image: Image,
+    pub wrap_mode: WrapMode,
+}
+
+impl ImageTexture {
+    pub fn new(image: Image, wrap_mode: WrapMode) -> Self {
+        Self { image, wrap_mode }
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P, wrap_mode: WrapMode) -> ImageResult<Self> {
+        Image::open(path).map(|image| Self::new(image, wrap_mode))
+    }
+
+    pub fn color_at_uv(&self, uv: Vec2) -> Color {
+        // pixel centers lie at half-integer coordinates
+        let x = uv.x * self.image.width() as Component - 0.5;
+        let y = (1.0 - uv.y) * self.image.height() as Component - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (dx, dy) = (x - x0, y - y0);
+
+        let pixel = |x: Component, y: Component| {
+            self.image.pixel(
+                self.wrap_mode.wrap(x as i64, self.image.width()),
+                self.wrap_mode.wrap(y as i64, self.image.height()),
+            )
+        };
+        let top = pixel(x0, y0) * (1.0 - dx) + pixel(x0 + 1.0, y0) * dx;
+        let bottom = pixel(x0, y0 + 1.0) * (1.0 - dx) + pixel(x0 + 1.0, y0 + 1.0) * dx;
+        top * (1.0 - dy) + bottom * dy
+    }
+}
+
+impl<V: Vector> Texture<V> for ImageTexture {
+    fn color_at(&self, hit: &Hit<V>) -> Color {
+        self.color_at_uv(hit.uv)
+    }
+}
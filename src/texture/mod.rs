@@ -0,0 +1,56 @@
+mod checkerboard;
+mod color_ramp;
+mod image_texture;
+mod noise;
+mod perlin;
+mod simplex;
+mod worley;
+
+pub use checkerboard::*;
+pub use color_ramp::*;
+pub use image_texture::*;
+pub use noise::*;
+pub use perlin::*;
+pub use simplex::*;
+pub use worley::*;
+
+use super::*;
+use std::sync::Arc;
+
+/// A color that varies across surfaces, evaluated wherever a ray hits.
+pub trait Texture<V: Vector>: 'static + Send + Sync {
+    fn color_at(&self, hit: &Hit<V>) -> Color;
+
+    /// for textures used as scalar parameters, e.g. heights
+    fn value_at(&self, hit: &Hit<V>) -> Component {
+        self.color_at(hit).luminance()
+    }
+}
+
+/// a constant color
+impl<V: Vector> Texture<V> for Color {
+    fn color_at(&self, _hit: &Hit<V>) -> Color {
+        *self
+    }
+}
+
+/// a constant value, e.g. for scalar parameters
+impl<V: Vector> Texture<V> for Component {
+    fn color_at(&self, _hit: &Hit<V>) -> Color {
+        Color::new_gray(*self, 1.0)
+    }
+
+    fn value_at(&self, _hit: &Hit<V>) -> Component {
+        *self
+    }
+}
+
+impl<V: Vector, T: Texture<V> + ?Sized> Texture<V> for Arc<T> {
+    fn color_at(&self, hit: &Hit<V>) -> Color {
+        (**self).color_at(hit)
+    }
+
+    fn value_at(&self, hit: &Hit<V>) -> Component {
+        (**self).value_at(hit)
+    }
+}
@@ -0,0 +1,116 @@
+use super::*;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+/// A smoothly varying random value throughout 3D space.
+pub trait Noise: 'static + Send + Sync {
+    fn noise(&self, point: Vec3) -> Component;
+}
+
+/// A shuffled lookup table used to hash lattice points, shared by the gradient noises.
+pub(super) struct Permutation([u8; 512]);
+
+impl Permutation {
+    pub(super) fn new(seed: u64) -> Self {
+        let mut values: Vec<u8> = (0..=255).collect();
+        values.shuffle(&mut StdRng::seed_from_u64(seed));
+        let mut permutation = [0; 512];
+        for (i, value) in permutation.iter_mut().enumerate() {
+            *value = values[i % 256];
+        }
+        Self(permutation)
+    }
+
+    /// coordinates must be in `0..=256`
+    pub(super) fn hash(&self, x: usize, y: usize, z: usize) -> u8 {
+        let p = &self.0;
+        p[p[p[x] as usize + y] as usize + z]
+    }
+}
+
+/// maps a lattice coordinate into the range the permutation table repeats over
+pub(super) fn wrap(coordinate: Component) -> usize {
+    coordinate as i64 as usize & 255
+}
+
+/// Fractal Brownian motion: sums octaves of noise at increasing frequencies and decreasing amplitudes.
+///
+/// Stays in the same range as the underlying noise.
+pub struct FractalNoise<N> {
+    pub noise: N,
+    pub octaves: usize,
+    /// how much the frequency grows with each octave
+    pub lacunarity: Component,
+    /// how much the amplitude shrinks with each octave
+    pub gain: Component,
+}
+
+impl<N: Noise> FractalNoise<N> {
+    pub fn new(noise: N, octaves: usize) -> Self {
+        Self {
+            noise,
+            octaves,
+            lacunarity: 2.0,
+            gain: 0.5,
+        }
+    }
+
+    fn sum_octaves(&self, point: Vec3, transform: impl Fn(Component) -> Component) -> Component {
+        let mut sum = 0.0;
+        let mut total_amplitude = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        for octave in 0..self.octaves {
+            // shift octaves apart so their lattices don't line up at the origin
+            let shift = Vec3::uniform(octave as Component * 17.31);
+            sum += amplitude * transform(self.noise.noise(point * frequency + shift));
+            total_amplitude += amplitude;
+            amplitude *= self.gain;
+            frequency *= self.lacunarity;
+        }
+        if total_amplitude > 0.0 {
+            sum / total_amplitude
+        } else {
+            0.0
+        }
+    }
+}
+
+impl<N: Noise> Noise for FractalNoise<N> {
+    fn noise(&self, point: Vec3) -> Component {
+        self.sum_octaves(point, |value| value)
+    }
+}
+
+/// Like fractal noise, but summing the absolute values of the octaves, resulting in sharp creases.
+///
+/// Intended for signed noise, producing values in `0.0..=1.0`.
+pub struct Turbulence<N>(pub FractalNoise<N>);
+
+impl<N: Noise> Turbulence<N> {
+    pub fn new(noise: N, octaves: usize) -> Self {
+        Self(FractalNoise::new(noise, octaves))
+    }
+}
+
+impl<N: Noise> Noise for Turbulence<N> {
+    fn noise(&self, point: Vec3) -> Component {
+        self.0.sum_octaves(point, Component::abs)
+    }
+}
+
+/// Colors surfaces by mapping noise sampled at the hit position through a color ramp.
+pub struct NoiseTexture<N> {
+    pub noise: N,
+    /// how many noise features to fit into one unit of world space
+    pub frequency: Component,
+    pub ramp: ColorRamp,
+}
+
+impl<N: Noise> Texture<Vec3> for NoiseTexture<N> {
+    fn color_at(&self, hit: &Hit<Vec3>) -> Color {
+        let value = self.noise.noise(hit.intersection * self.frequency);
+        self.ramp.color_at(value)
+    }
+}
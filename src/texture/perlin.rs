@@ -0,0 +1,65 @@
+use super::*;
+
+/// Ken Perlin's improved gradient noise, in `-1.0..=1.0`.
+pub struct PerlinNoise {
+    permutation: Permutation,
+}
+
+impl PerlinNoise {
+    /// the same seed always produces the same noise
+    pub fn new(seed: u64) -> Self {
+        Self {
+            permutation: Permutation::new(seed),
+        }
+    }
+}
+
+impl Noise for PerlinNoise {
+    fn noise(&self, point: Vec3) -> Component {
+        let floor = Vec3::new(point.x.floor(), point.y.floor(), point.z.floor());
+        let (x, y, z) = (wrap(floor.x), wrap(floor.y), wrap(floor.z));
+        let local = point - floor;
+        let u = fade(local.x);
+        let v = fade(local.y);
+        let w = fade(local.z);
+
+        let corner = |dx: usize, dy: usize, dz: usize| {
+            let offset = Vec3::new(dx as Component, dy as Component, dz as Component);
+            let hash = self.permutation.hash(x + dx, y + dy, z + dz);
+            gradient(hash, local - offset)
+        };
+        lerp(
+            lerp(
+                lerp(corner(0, 0, 0), corner(1, 0, 0), u),
+                lerp(corner(0, 1, 0), corner(1, 1, 0), u),
+                v,
+            ),
+            lerp(
+                lerp(corner(0, 0, 1), corner(1, 0, 1), u),
+                lerp(corner(0, 1, 1), corner(1, 1, 1), u),
+                v,
+            ),
+            w,
+        )
+    }
+}
+
+fn fade(t: Component) -> Component {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: Component, b: Component, factor: Component) -> Component {
+    a + (b - a) * factor
+}
+
+/// dot product with one of 12 gradients pointing to the edges of a cube
+pub(super) fn gradient(hash: u8, offset: Vec3) -> Component {
+    let h = hash & 15;
+    let u = if h < 8 { offset.x } else { offset.y };
+    let v = match h {
+        0..=3 => offset.y,
+        12 | 14 => offset.x,
+        _ => offset.z,
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
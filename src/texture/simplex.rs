@@ -0,0 +1,69 @@
+use super::*;
+
+/// Simplex noise, in `-1.0..=1.0`: similar to Perlin noise, but cheaper and without axis-aligned artifacts.
+pub struct SimplexNoise {
+    permutation: Permutation,
+}
+
+impl SimplexNoise {
+    /// the same seed always produces the same noise
+    pub fn new(seed: u64) -> Self {
+        Self {
+            permutation: Permutation::new(seed),
+        }
+    }
+}
+
+const SKEW: Component = 1.0 / 3.0;
+const UNSKEW: Component = 1.0 / 6.0;
+
+impl Noise for SimplexNoise {
+    // based on Stefan Gustavson's "Simplex noise demystified"
+    fn noise(&self, point: Vec3) -> Component {
+        // find the cell in skewed space, then the simplex within it
+        let skew = (point.x + point.y + point.z) * SKEW;
+        let cell = Vec3::new(
+            (point.x + skew).floor(),
+            (point.y + skew).floor(),
+            (point.z + skew).floor(),
+        );
+        let unskew = (cell.x + cell.y + cell.z) * UNSKEW;
+        let offset0 = point - (cell - Vec3::uniform(unskew));
+
+        let (step1, step2) = if offset0.x >= offset0.y {
+            if offset0.y >= offset0.z {
+                ((1, 0, 0), (1, 1, 0))
+            } else if offset0.x >= offset0.z {
+                ((1, 0, 0), (1, 0, 1))
+            } else {
+                ((0, 0, 1), (1, 0, 1))
+            }
+        } else if offset0.y < offset0.z {
+            ((0, 0, 1), (0, 1, 1))
+        } else if offset0.x < offset0.z {
+            ((0, 1, 0), (0, 1, 1))
+        } else {
+            ((0, 1, 0), (1, 1, 0))
+        };
+
+        let (x, y, z) = (wrap(cell.x), wrap(cell.y), wrap(cell.z));
+        let corner = |(dx, dy, dz): (usize, usize, usize), index: Component| {
+            let step = Vec3::new(dx as Component, dy as Component, dz as Component);
+            let offset = offset0 - step + Vec3::uniform(UNSKEW * index);
+            let falloff = 0.6 - offset.squared_sum();
+            if falloff < 0.0 {
+                0.0
+            } else {
+                let hash = self.permutation.hash(x + dx, y + dy, z + dz);
+                falloff.powi(4) * perlin::gradient(hash, offset)
+            }
+        };
+
+        let sum = corner((0, 0, 0), 0.0)
+            + corner(step1, 1.0)
+            + corner(step2, 2.0)
+            + corner((1, 1, 1), 3.0);
+        // scale to roughly fill -1.0..=1.0
+        32.0 * sum
+    }
+}
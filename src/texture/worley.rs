@@ -0,0 +1,86 @@
+use super::*;
+
+/// Which distance cellular noise reports.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum WorleyFeature {
+    /// distance to the closest feature point, giving round cells
+    Closest,
+    /// distance to the second closest feature point
+    SecondClosest,
+    /// difference between the two, giving sharp cell borders
+    Border,
+}
+
+/// Steven Worley's cellular noise, with one feature point scattered randomly in each unit cube.
+///
+/// Values start at 0 and rarely exceed 1 (for the closest feature point).
+pub struct WorleyNoise {
+    seed: u64,
+    pub feature: WorleyFeature,
+}
+
+impl WorleyNoise {
+    /// the same seed always produces the same noise
+    pub fn new(seed: u64, feature: WorleyFeature) -> Self {
+        Self { seed, feature }
+    }
+
+    fn feature_point(&self, cell: (i64, i64, i64)) -> Vec3 {
+        let (x, y, z) = cell;
+        let mut hash = self.seed;
+        for coordinate in [x, y, z] {
+            hash = mix(hash ^ coordinate as u64);
+        }
+        let mut next = || {
+            hash = mix(hash);
+            (hash >> 40) as Component / (1u64 << 24) as Component
+        };
+        Vec3::new(
+            x as Component + next(),
+            y as Component + next(),
+            z as Component + next(),
+        )
+    }
+}
+
+/// splitmix64's finalizer, which scrambles its input thoroughly
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+impl Noise for WorleyNoise {
+    fn noise(&self, point: Vec3) -> Component {
+        let (x, y, z) = (
+            point.x.floor() as i64,
+            point.y.floor() as i64,
+            point.z.floor() as i64,
+        );
+
+        let mut closest = Component::INFINITY;
+        let mut second_closest = Component::INFINITY;
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let feature = self.feature_point((x + dx, y + dy, z + dz));
+                    let distance = (feature - point).squared_sum();
+                    if distance < closest {
+                        second_closest = closest;
+                        closest = distance;
+                    } else if distance < second_closest {
+                        second_closest = distance;
+                    }
+                }
+            }
+        }
+
+        let (closest, second_closest) = (closest.sqrt(), second_closest.sqrt());
+        match self.feature {
+            WorleyFeature::Closest => closest,
+            WorleyFeature::SecondClosest => second_closest,
+            WorleyFeature::Border => second_closest - closest,
+        }
+    }
+}
@@ -2,36 +2,32 @@ use super::*;
 
 #[derive(Clone)]
 pub struct TracingOptions {
+    /// Seen where rays leave the scene, on top of lights surrounding it like environment maps.
+    /// Its alpha is kept either way, so a clear background still renders lit but transparent.
     pub background_color: Color,
+    /// the most bounces a path can take, on top of the limits for each kind of bounce
     pub max_bounces: usize,
+    pub max_diffuse_bounces: usize,
+    /// including perfectly specular reflections
+    pub max_glossy_bounces: usize,
+    pub max_transmission_bounces: usize,
+    /// Paths that have bounced more often than this are ended at random, more likely the less light they carry.
+    /// The light of those that go on is scaled up to make up for it, so unlike the limits this doesn't darken the image.
+    pub russian_roulette_depth: usize,
     pub near_clipping: Component,
+    pub light_selection: LightSelection,
 }
 
-pub struct Raytracer<V: Vector, C: Camera<V = V>, E: SceneElement<V = V>> {
+pub struct Raytracer<V: Vector, C: Camera<V = V>, E: Scene<V = V>> {
     pub camera: C,
-    pub element: E,
+    pub scene: E,
+    pub integrator: Box<dyn Integrator<E>>,
 }
 
-impl<V: Vector, C: Camera<V = V>, E: SceneElement<V = V>> Raytracer<V, C, E> {
+impl<V: Vector, C: Camera<V = V>, E: Scene<V = V>> Raytracer<V, C, E> {
+    /// the light reaching the camera through a random point of the area, found by the integrator
     pub fn trace(&self, area: &VectorArea<Vec2>, options: &TracingOptions) -> Color {
-        let ray = self.camera.ray(area);
-        self.rec_trace(ray, options, options.max_bounces)
-    }
-
-    fn rec_trace(&self, ray: Ray<V>, options: &TracingOptions, bounces_left: usize) -> Color {
-        let mut color = options.background_color;
-
-        if let Some(intersection) = self.element.first_intersection(ray, options.near_clipping) {
-            let behavior = intersection.data;
-            color = behavior.emission;
-
-            if bounces_left > 0 {
-                if let Some(next_bounce) = behavior.next_bounce {
-                    color += behavior.color * self.rec_trace(next_bounce, options, bounces_left - 1);
-                }
-            }
-        }
-
-        color
+        self.integrator
+            .radiance(&self.scene, self.camera.ray(area), options)
     }
 }
@@ -0,0 +1,103 @@
+use super::*;
+
+/// Places a shape or scene element in the world using a transform from its object space.
+///
+/// Rays are mapped into object space for intersection, and the results are mapped back out.
+/// Wrap an `Arc` to instance the same (possibly heavy) object many times without copying it.
+pub struct Transformed<T> {
+    pub inner: T,
+    pub transform: Transform,
+}
+
+impl<T> Transformed<T> {
+    pub fn new(inner: T, transform: Transform) -> Self {
+        Self { inner, transform }
+    }
+
+    fn object_space_ray(&self, ray: &Ray<Vec3>) -> (Ray<Vec3>, Component) {
+        self.transform.inverse().ray(ray)
+    }
+
+    fn world_space_hit(&self, ray: &Ray<Vec3>, distance: Component, hit: Hit<Vec3>) -> Hit<Vec3> {
+        Hit {
+            ray_direction: ray.direction,
+            intersection: ray.at(distance),
+            normal: self.transform.normal(hit.normal),
+            geometric_normal: self.transform.normal(hit.geometric_normal),
+            uv: hit.uv,
+            tangent: self.transform.vector(hit.tangent).normalized(),
+            bitangent: self.transform.vector(hit.bitangent).normalized(),
+        }
+    }
+}
+
+impl<S: Shape<V = Vec3>> Shape for Transformed<S> {
+    type V = Vec3;
+
+    fn first_intersection(
+        &self,
+        ray: &Ray<Vec3>,
+        near_clipping: Component,
+    ) -> Option<Intersection<Hit<Vec3>>> {
+        let (object_ray, scale) = self.object_space_ray(ray);
+        self.inner
+            .first_intersection(&object_ray, near_clipping * scale)
+            .map(|i| {
+                let distance = i.distance / scale;
+                Intersection {
+                    distance,
+                    data: self.world_space_hit(ray, distance, i.data),
+                }
+            })
+    }
+
+    fn occluded(&self, ray: &Ray<Vec3>, near_clipping: Component, far_clipping: Component) -> bool {
+        let (object_ray, scale) = self.object_space_ray(ray);
+        self.inner
+            .occluded(&object_ray, near_clipping * scale, far_clipping * scale)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox<Vec3>> {
+        self.inner.bounds().map(|b| b.transformed(&self.transform))
+    }
+}
+
+impl<E: SceneElement<V = Vec3>> SceneElement for Transformed<E> {
+    type V = Vec3;
+
+    fn first_intersection(
+        &self,
+        ray: Ray<Vec3>,
+        near_clipping: Component,
+    ) -> ElementIntersection<'_, Vec3> {
+        let (object_ray, scale) = self.object_space_ray(&ray);
+        self.inner
+            .first_intersection(object_ray, near_clipping * scale)
+            .map(|i| {
+                let distance = i.distance / scale;
+                Intersection {
+                    distance,
+                    data: SurfaceHit {
+                        hit: self.world_space_hit(&ray, distance, i.data.hit),
+                        material: i.data.material,
+                        // a light sampled in object space would be in the wrong place, so emission is counted on hits instead
+                        light: None,
+                        visibility: i.data.visibility,
+                        light_links: i.data.light_links,
+                        element: i.data.element,
+                        material_id: i.data.material_id,
+                    },
+                }
+            })
+    }
+
+    fn occluded(&self, ray: Ray<Vec3>, near_clipping: Component, far_clipping: Component) -> bool {
+        let (object_ray, scale) = self.object_space_ray(&ray);
+        self.inner
+            .occluded(object_ray, near_clipping * scale, far_clipping * scale)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox<Vec3>> {
+        self.inner.bounds().map(|b| b.transformed(&self.transform))
+    }
+}
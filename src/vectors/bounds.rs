@@ -0,0 +1,102 @@
+use super::*;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BoundingBox<V: Vector> {
+    pub min: V,
+    pub max: V,
+}
+
+impl<V: Vector> BoundingBox<V> {
+    pub fn new(min: V, max: V) -> Self {
+        Self { min, max }
+    }
+
+    pub fn around_point(point: V) -> Self {
+        Self::new(point, point)
+    }
+
+    pub fn around_points<I: IntoIterator<Item = V>>(points: I) -> Option<Self> {
+        points
+            .into_iter()
+            .map(Self::around_point)
+            .reduce(|l, r| l.union(&r))
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self::new(
+            self.min.component_min(other.min),
+            self.max.component_max(other.max),
+        )
+    }
+
+    pub fn center(&self) -> V {
+        self.min.lerp(self.max, 0.5)
+    }
+
+    pub fn extent(&self) -> V {
+        self.max - self.min
+    }
+
+    pub fn longest_axis(&self) -> usize {
+        let extent = self.extent();
+        (0..V::DIMENSIONS)
+            .max_by(|&l, &r| {
+                extent
+                    .component(l)
+                    .partial_cmp(&extent.component(r))
+                    .unwrap()
+            })
+            .unwrap()
+    }
+
+    /// the range of distances along the ray that lies within the box, clamped to `near..far`
+    pub fn intersection_range(
+        &self,
+        ray: &Ray<V>,
+        near: Component,
+        far: Component,
+    ) -> Option<(Component, Component)> {
+        let mut near = near;
+        let mut far = far;
+        for axis in 0..V::DIMENSIONS {
+            let origin = ray.origin.component(axis);
+            let inverse_direction = 1.0 / ray.direction.component(axis);
+            let mut t0 = (self.min.component(axis) - origin) * inverse_direction;
+            let mut t1 = (self.max.component(axis) - origin) * inverse_direction;
+            if t0 > t1 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            // NaN (ray parallel to and exactly on a slab) compares false here, leaving the range unchanged
+            if t0 > near {
+                near = t0;
+            }
+            if t1 < far {
+                far = t1;
+            }
+            if near > far {
+                return None;
+            }
+        }
+        Some((near, far))
+    }
+}
+
+impl BoundingBox<Vec3> {
+    pub fn corners(&self) -> [Vec3; 8] {
+        let (min, max) = (self.min, self.max);
+        [
+            Vec3::new(min.x, min.y, min.z),
+            Vec3::new(min.x, min.y, max.z),
+            Vec3::new(min.x, max.y, min.z),
+            Vec3::new(min.x, max.y, max.z),
+            Vec3::new(max.x, min.y, min.z),
+            Vec3::new(max.x, min.y, max.z),
+            Vec3::new(max.x, max.y, min.z),
+            Vec3::new(max.x, max.y, max.z),
+        ]
+    }
+
+    pub fn transformed(&self, transform: &Transform) -> Self {
+        Self::around_points(self.corners().iter().map(|&c| transform.point(c))).unwrap()
+    }
+}
@@ -0,0 +1,223 @@
+use super::*;
+use std::fmt;
+use std::ops;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Matrix4 {
+    pub rows: [[Component; 4]; 4],
+}
+
+impl Matrix4 {
+    pub fn new(rows: [[Component; 4]; 4]) -> Self {
+        Self { rows }
+    }
+
+    pub fn identity() -> Self {
+        Self::new([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn translation(offset: Vec3) -> Self {
+        Self::new([
+            [1.0, 0.0, 0.0, offset.x],
+            [0.0, 1.0, 0.0, offset.y],
+            [0.0, 0.0, 1.0, offset.z],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn scaling(factors: Vec3) -> Self {
+        Self::new([
+            [factors.x, 0.0, 0.0, 0.0],
+            [0.0, factors.y, 0.0, 0.0],
+            [0.0, 0.0, factors.z, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// counterclockwise rotation around `axis` (when looking against its direction), using Rodrigues' formula
+    pub fn rotation(axis: Normalized<Vec3>, angle: Component) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        let t = 1.0 - cos;
+        let Vec3 { x, y, z } = *axis;
+        Self::new([
+            [
+                t * x * x + cos,
+                t * x * y - sin * z,
+                t * x * z + sin * y,
+                0.0,
+            ],
+            [
+                t * x * y + sin * z,
+                t * y * y + cos,
+                t * y * z - sin * x,
+                0.0,
+            ],
+            [
+                t * x * z - sin * y,
+                t * y * z + sin * x,
+                t * z * z + cos,
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn transposed(&self) -> Self {
+        let mut rows = [[0.0; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.rows[j][i];
+            }
+        }
+        Self::new(rows)
+    }
+
+    /// Gauss-Jordan elimination with partial pivoting; `None` if the matrix is singular
+    pub fn inverse(&self) -> Option<Self> {
+        let mut lhs = self.rows;
+        let mut rhs = Self::identity().rows;
+
+        for column in 0..4 {
+            let pivot = (column..4)
+                .max_by(|&l, &r| {
+                    lhs[l][column]
+                        .abs()
+                        .partial_cmp(&lhs[r][column].abs())
+                        .unwrap()
+                })
+                .unwrap();
+            if lhs[pivot][column].abs() < Component::EPSILON {
+                return None;
+            }
+            lhs.swap(column, pivot);
+            rhs.swap(column, pivot);
+
+            let factor = 1.0 / lhs[column][column];
+            for j in 0..4 {
+                lhs[column][j] *= factor;
+                rhs[column][j] *= factor;
+            }
+
+            for row in 0..4 {
+                if row == column {
+                    continue;
+                }
+                let factor = lhs[row][column];
+                for j in 0..4 {
+                    lhs[row][j] -= factor * lhs[column][j];
+                    rhs[row][j] -= factor * rhs[column][j];
+                }
+            }
+        }
+
+        Some(Self::new(rhs))
+    }
+
+    pub fn transform_point(&self, point: Vec3) -> Vec3 {
+        let result = *self * Vec4::new(point.x, point.y, point.z, 1.0);
+        Vec3::new(result.x, result.y, result.z) / result.w
+    }
+
+    /// applies only the linear part, ignoring translation
+    pub fn transform_vector<R: Into<Vec3>>(&self, vector: R) -> Vec3 {
+        let vector: Vec3 = vector.into();
+        let result = *self * Vec4::new(vector.x, vector.y, vector.z, 0.0);
+        Vec3::new(result.x, result.y, result.z)
+    }
+}
+
+impl fmt::Display for Matrix4 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows: Vec<String> = self
+            .rows
+            .iter()
+            .map(|row| Vec4::new(row[0], row[1], row[2], row[3]).to_string())
+            .collect();
+        write!(f, "[{}]", rows.join(", "))
+    }
+}
+
+impl ops::Mul for Matrix4 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let mut rows = [[0.0; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.rows[i][k] * rhs.rows[k][j]).sum();
+            }
+        }
+        Self::new(rows)
+    }
+}
+
+impl ops::Mul<Vec4> for Matrix4 {
+    type Output = Vec4;
+
+    fn mul(self, rhs: Vec4) -> Vec4 {
+        let row = |i: usize| {
+            Vec4::new(
+                self.rows[i][0],
+                self.rows[i][1],
+                self.rows[i][2],
+                self.rows[i][3],
+            )
+        };
+        Vec4::new(
+            row(0).dot(rhs),
+            row(1).dot(rhs),
+            row(2).dot(rhs),
+            row(3).dot(rhs),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: Matrix4, expected: Matrix4) {
+        for (actual_row, expected_row) in actual.rows.iter().zip(&expected.rows) {
+            for (actual, expected) in actual_row.iter().zip(expected_row) {
+                assert!(
+                    (actual - expected).abs() < 1e-5,
+                    "{} != {}",
+                    actual,
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn inverse_undoes_the_matrix() {
+        let matrix = Matrix4::translation(Vec3::new(1.0, -2.0, 3.0))
+            * Matrix4::rotation(Vec3::new(1.0, 2.0, -0.5).normalized(), 0.7)
+            * Matrix4::scaling(Vec3::new(2.0, 0.5, -4.0));
+        let inverse = matrix.inverse().unwrap();
+        assert_close(matrix * inverse, Matrix4::identity());
+        assert_close(inverse * matrix, Matrix4::identity());
+    }
+
+    #[test]
+    fn inverse_pivots_past_zeros_on_the_diagonal() {
+        // swaps the first two axes, which elimination without pivoting would divide by zero on
+        let matrix = Matrix4::new([
+            [0.0, 1.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        assert_close(matrix.inverse().unwrap(), matrix);
+    }
+
+    #[test]
+    fn singular_matrices_have_no_inverse() {
+        assert_eq!(Matrix4::scaling(Vec3::new(1.0, 0.0, 1.0)).inverse(), None);
+    }
+}
@@ -1,9 +1,15 @@
 mod area;
+mod bounds;
+mod matrix;
 mod normalized;
 mod ray;
+mod transform;
 mod vector;
 
 pub use area::*;
+pub use bounds::*;
+pub use matrix::*;
 pub use normalized::*;
 pub use ray::*;
+pub use transform::*;
 pub use vector::*;
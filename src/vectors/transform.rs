@@ -0,0 +1,101 @@
+use super::*;
+
+/// An invertible affine transform, keeping its inverse around to map back and forth cheaply.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transform {
+    matrix: Matrix4,
+    inverse: Matrix4,
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Self {
+            matrix: Matrix4::identity(),
+            inverse: Matrix4::identity(),
+        }
+    }
+
+    /// `None` if the matrix is not invertible
+    pub fn from_matrix(matrix: Matrix4) -> Option<Self> {
+        matrix.inverse().map(|inverse| Self { matrix, inverse })
+    }
+
+    pub fn translation(offset: Vec3) -> Self {
+        Self {
+            matrix: Matrix4::translation(offset),
+            inverse: Matrix4::translation(-offset),
+        }
+    }
+
+    pub fn scaling(factors: Vec3) -> Self {
+        Self {
+            matrix: Matrix4::scaling(factors),
+            inverse: Matrix4::scaling(Vec3::new(1.0 / factors.x, 1.0 / factors.y, 1.0 / factors.z)),
+        }
+    }
+
+    pub fn uniform_scaling(factor: Component) -> Self {
+        Self::scaling(Vec3::new(factor, factor, factor))
+    }
+
+    pub fn rotation(axis: Normalized<Vec3>, angle: Component) -> Self {
+        let matrix = Matrix4::rotation(axis, angle);
+        Self {
+            matrix,
+            inverse: matrix.transposed(),
+        }
+    }
+
+    pub fn matrix(&self) -> &Matrix4 {
+        &self.matrix
+    }
+
+    pub fn inverse_matrix(&self) -> &Matrix4 {
+        &self.inverse
+    }
+
+    pub fn inverse(&self) -> Self {
+        Self {
+            matrix: self.inverse,
+            inverse: self.matrix,
+        }
+    }
+
+    /// the transform that applies `self` first, then `next`
+    pub fn then(&self, next: &Self) -> Self {
+        Self {
+            matrix: next.matrix * self.matrix,
+            inverse: self.inverse * next.inverse,
+        }
+    }
+
+    pub fn point(&self, point: Vec3) -> Vec3 {
+        self.matrix.transform_point(point)
+    }
+
+    pub fn vector<R: Into<Vec3>>(&self, vector: R) -> Vec3 {
+        self.matrix.transform_vector(vector)
+    }
+
+    /// normals transform with the inverse transpose to stay perpendicular to the surface
+    pub fn normal(&self, normal: Normalized<Vec3>) -> Normalized<Vec3> {
+        self.inverse
+            .transposed()
+            .transform_vector(normal)
+            .normalized()
+    }
+
+    /// Maps a ray through this transform, also returning how much longer the direction got.
+    /// Distances along the original ray are multiplied by that factor to get distances along the new one.
+    pub fn ray(&self, ray: &Ray<Vec3>) -> (Ray<Vec3>, Component) {
+        let direction = self.vector(ray.direction);
+        let scale = direction.norm();
+        (
+            Ray {
+                origin: self.point(ray.origin),
+                direction: Normalized(direction / scale),
+            },
+            scale,
+        )
+    }
+}
@@ -30,8 +30,18 @@ pub trait Vector:
     + ops::Div<Component, Output = Self>
     + ops::DivAssign<Component>
 {
+    const DIMENSIONS: usize;
+
     fn zero() -> Self;
 
+    fn uniform(value: Component) -> Self;
+
+    fn component(self, axis: usize) -> Component;
+
+    fn component_min(self, other: Self) -> Self;
+
+    fn component_max(self, other: Self) -> Self;
+
     fn squared_sum(self) -> Component {
         self.dot(self)
     }
@@ -76,10 +86,28 @@ macro_rules! vec_type {
         }
 
         impl Vector for $type {
+            const DIMENSIONS: usize = [$(stringify!($component)),*].len();
+
             fn zero() -> Self {
                 Self { $($component: 0.0),* }
             }
 
+            fn uniform(value: Component) -> Self {
+                Self { $($component: value),* }
+            }
+
+            fn component(self, axis: usize) -> Component {
+                [$(self.$component),*][axis]
+            }
+
+            fn component_min(self, other: Self) -> Self {
+                Self { $($component: self.$component.min(other.$component)),* }
+            }
+
+            fn component_max(self, other: Self) -> Self {
+                Self { $($component: self.$component.max(other.$component)),* }
+            }
+
             fn dot<R: Into<Self>>(self, rhs: R) -> Component {
                 let rhs: Self = rhs.into();
                 0.0 $(+ self.$component * rhs.$component)*
@@ -0,0 +1,136 @@
+use super::*;
+use std::collections::HashSet;
+
+/// What a ray is looking for, which decides the elements it can see.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RayKind {
+    Camera,
+    /// leaving a surface on the side the previous ray came from
+    Reflection,
+    /// passing through a surface
+    Refraction,
+    /// checking whether a light is blocked
+    Shadow,
+}
+
+/// The kinds of rays that see an element; by default, all of them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Visibility {
+    pub camera: bool,
+    /// whether the element blocks light from reaching other surfaces
+    pub shadows: bool,
+    pub reflections: bool,
+    pub refractions: bool,
+}
+
+impl Visibility {
+    pub fn sees(&self, kind: RayKind) -> bool {
+        match kind {
+            RayKind::Camera => self.camera,
+            RayKind::Reflection => self.reflections,
+            RayKind::Refraction => self.refractions,
+            RayKind::Shadow => self.shadows,
+        }
+    }
+
+    /// only what's seen by both
+    fn intersection(&self, other: &Self) -> Self {
+        Self {
+            camera: self.camera && other.camera,
+            shadows: self.shadows && other.shadows,
+            reflections: self.reflections && other.reflections,
+            refractions: self.refractions && other.refractions,
+        }
+    }
+}
+
+impl Default for Visibility {
+    fn default() -> Self {
+        Self {
+            camera: true,
+            shadows: true,
+            reflections: true,
+            refractions: true,
+        }
+    }
+}
+
+/// The lights that illuminate an element, given by the ids `Scene::add_light` returns.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum LightLinks {
+    #[default]
+    All,
+    Only(HashSet<LightId>),
+    Except(HashSet<LightId>),
+}
+
+impl LightLinks {
+    pub fn includes(&self, light: LightId) -> bool {
+        match self {
+            LightLinks::All => true,
+            LightLinks::Only(lights) => lights.contains(&light),
+            LightLinks::Except(lights) => !lights.contains(&light),
+        }
+    }
+}
+
+/// Limits which rays see an element and which lights illuminate it, e.g. for look development.
+///
+/// Wrapping an area light keeps it lighting the scene while hiding its surface, as long as it's added with `Scene::add_area_light`.
+/// When wrappers are nested, the element is only seen by rays all of them let through, and the innermost light links apply.
+pub struct Restricted<E> {
+    pub element: E,
+    pub visibility: Visibility,
+    pub light_links: LightLinks,
+}
+
+impl<E: SceneElement> SceneElement for Restricted<E> {
+    type V = E::V;
+
+    fn first_intersection(
+        &self,
+        ray: Ray<Self::V>,
+        near_clipping: Component,
+    ) -> ElementIntersection<'_, Self::V> {
+        self.element
+            .first_intersection(ray, near_clipping)
+            .map(|mut i| {
+                i.data.visibility = i.data.visibility.intersection(&self.visibility);
+                i.data.light_links = i.data.light_links.or(Some(&self.light_links));
+                i
+            })
+    }
+
+    fn occluded(
+        &self,
+        ray: Ray<Self::V>,
+        near_clipping: Component,
+        far_clipping: Component,
+    ) -> bool {
+        self.visibility.shadows && self.element.occluded(ray, near_clipping, far_clipping)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox<Self::V>> {
+        self.element.bounds()
+    }
+}
+
+impl<L: Light> Light for Restricted<L> {
+    type V = L::V;
+
+    fn sample(&self, point: Self::V) -> Option<LightSample<Self::V>> {
+        self.element.sample(point)
+    }
+
+    fn escaped_radiance(&self, direction: Normalized<Self::V>) -> Color {
+        self.element.escaped_radiance(direction)
+    }
+
+    fn pdf(&self, point: Self::V, direction: Normalized<Self::V>) -> Component {
+        self.element.pdf(point, direction)
+    }
+
+    fn bounds(&self) -> Option<LightBounds<Self::V>> {
+        Light::bounds(&self.element)
+    }
+}